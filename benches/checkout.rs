@@ -0,0 +1,18 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use futures::executor::block_on;
+use lazy_pool::Pool;
+
+fn hot_pool_checkout(c: &mut Criterion) {
+    let pool = block_on(Pool::new_eager(4, Box::new(|| 0u64))).unwrap();
+    let mut group = c.benchmark_group("hot pool checkout");
+    group.bench_function("get", |b| {
+        b.iter(|| block_on(async { pool.get().await.release().await.unwrap() }))
+    });
+    group.bench_function("get_or_spin(100)", |b| {
+        b.iter(|| block_on(async { pool.get_or_spin(100).await.release().await.unwrap() }))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, hot_pool_checkout);
+criterion_main!(benches);
@@ -0,0 +1,121 @@
+use crate::{LazyPoolError, PoolStats, Result};
+use std::time::Duration;
+
+/// Usage of a pool on an auto-scaling cycle, see [`crate::Pool::auto_scale`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AutoScaleSignals {
+    pub stats: PoolStats,
+    /// Mean time checkouts spent waiting for an object since the last cycle.
+    pub mean_wait: Duration,
+    /// How long the longest idle object has been idle, if any is idle and
+    /// the idle objects aren't locked by a waiting checkout.
+    pub longest_idle: Option<Duration>,
+}
+
+/// Decides the size of a pool from its usage, see [`crate::Pool::auto_scale`].
+pub trait AutoScaleLogic {
+    /// Returns the size the pool should be resized to, if any.
+    fn target_size(&self, stats: PoolStats) -> Option<usize>;
+
+    /**
+    Same as [`AutoScaleLogic::target_size`], but also given wait and idle
+    times. This is what the pool calls; by default it only looks at the
+    stats.
+    */
+    fn target_size_from_signals(&self, signals: AutoScaleSignals) -> Option<usize> {
+        self.target_size(signals.stats)
+    }
+}
+
+impl<F> AutoScaleLogic for F
+where
+    F: Fn(PoolStats) -> Option<usize>,
+{
+    fn target_size(&self, stats: PoolStats) -> Option<usize> {
+        self(stats)
+    }
+}
+
+/**
+Sizes the pool so that `target_idle_fraction` of its capacity is left over
+after the objects in use, within `min` and `max`. For example, with a
+fraction of 0.2 and 8 objects checked out the pool is resized to 10.
+
+Given wait and idle times, the pool also grows by one object whenever
+checkouts waited longer than `scale_up_threshold` on average, and only
+shrinks once an object has been idle for at least `scale_down_threshold`.
+*/
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AutoScalePolicy {
+    pub target_idle_fraction: f32,
+    pub scale_up_threshold: Duration,
+    pub scale_down_threshold: Duration,
+    pub min: usize,
+    pub max: usize,
+}
+
+impl AutoScalePolicy {
+    /**
+    Creates a policy, failing with [`LazyPoolError::InvalidConfig`] if `min`
+    exceeds `max` or `target_idle_fraction` is not within `0.0..1.0`.
+    */
+    pub fn new(
+        target_idle_fraction: f32,
+        scale_up_threshold: Duration,
+        scale_down_threshold: Duration,
+        min: usize,
+        max: usize,
+    ) -> Result<Self> {
+        if min > max {
+            return Err(LazyPoolError::InvalidConfig(
+                "minimum size must not exceed the maximum size",
+            ));
+        }
+        if !(0.0..1.0).contains(&target_idle_fraction) {
+            return Err(LazyPoolError::InvalidConfig(
+                "target idle fraction must be within 0.0..1.0",
+            ));
+        }
+        Ok(Self {
+            target_idle_fraction,
+            scale_up_threshold,
+            scale_down_threshold,
+            min,
+            max,
+        })
+    }
+
+    fn idle_target(&self, in_use: usize) -> usize {
+        let busy_fraction = 1.0 - self.target_idle_fraction.clamp(0.0, 0.99);
+        (in_use as f32 / busy_fraction).ceil() as usize
+    }
+
+    // Not `clamp`, which panics if a policy built without `new` has
+    // `min > max`.
+    fn bounded(&self, target: usize) -> usize {
+        target.max(self.min).min(self.max)
+    }
+}
+
+impl AutoScaleLogic for AutoScalePolicy {
+    fn target_size(&self, stats: PoolStats) -> Option<usize> {
+        let target = self.bounded(self.idle_target(stats.in_use));
+        (target != stats.capacity).then_some(target)
+    }
+
+    fn target_size_from_signals(&self, signals: AutoScaleSignals) -> Option<usize> {
+        let stats = signals.stats;
+        let mut target = self.idle_target(stats.in_use);
+        if signals.mean_wait > self.scale_up_threshold {
+            target = target.max(stats.capacity + 1);
+        }
+        let idle_long_enough = signals
+            .longest_idle
+            .is_some_and(|idle| idle >= self.scale_down_threshold);
+        if target < stats.capacity && !idle_long_enough {
+            target = stats.capacity;
+        }
+        let target = self.bounded(target);
+        (target != stats.capacity).then_some(target)
+    }
+}
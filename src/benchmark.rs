@@ -0,0 +1,34 @@
+use std::time::Duration;
+
+/// Factory latencies measured by [`crate::Pool::benchmark_factory`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FactoryBenchmark {
+    pub min: Duration,
+    pub max: Duration,
+    pub mean: Duration,
+    pub p50: Duration,
+    pub p95: Duration,
+}
+
+impl FactoryBenchmark {
+    pub(crate) fn from_samples(mut samples: Vec<Duration>) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        samples.sort();
+        let total: Duration = samples.iter().sum();
+        Self {
+            min: samples[0],
+            max: samples[samples.len() - 1],
+            mean: total / samples.len() as u32,
+            p50: percentile(&samples, 50),
+            p95: percentile(&samples, 95),
+        }
+    }
+}
+
+/// Nearest-rank percentile of sorted, non-empty samples.
+pub(crate) fn percentile(samples: &[Duration], percent: usize) -> Duration {
+    let rank = (samples.len() * percent).div_ceil(100);
+    samples[rank.saturating_sub(1)]
+}
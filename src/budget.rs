@@ -0,0 +1,53 @@
+use crate::{Pooled, Result};
+use std::{
+    ops::{Deref, DerefMut},
+    time::{Duration, Instant},
+};
+
+/**
+A checked out object along with the time budget of the operation it was
+checked out for, as returned by [`crate::Pool::get_within_budget`].
+*/
+pub struct BudgetedGet<T: Send + 'static> {
+    pooled: Pooled<T>,
+    started: Instant,
+    budget: Duration,
+}
+
+impl<T: Send + 'static> BudgetedGet<T> {
+    pub(crate) fn new(pooled: Pooled<T>, started: Instant, budget: Duration) -> Self {
+        Self {
+            pooled,
+            started,
+            budget,
+        }
+    }
+
+    /// Milliseconds left of the budget, counting from the start of the checkout.
+    pub fn remaining_budget_ms(&self) -> u64 {
+        let remaining = self.budget.saturating_sub(self.started.elapsed());
+        remaining.as_millis() as u64
+    }
+
+    pub fn into_inner(self) -> Pooled<T> {
+        self.pooled
+    }
+
+    pub async fn release(self) -> Result<()> {
+        self.pooled.release().await
+    }
+}
+
+impl<T: Send> Deref for BudgetedGet<T> {
+    type Target = Pooled<T>;
+
+    fn deref(&self) -> &Pooled<T> {
+        &self.pooled
+    }
+}
+
+impl<T: Send> DerefMut for BudgetedGet<T> {
+    fn deref_mut(&mut self) -> &mut Pooled<T> {
+        &mut self.pooled
+    }
+}
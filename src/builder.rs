@@ -0,0 +1,543 @@
+#[cfg(feature = "background-factory")]
+use crate::factory::{Background, BackgroundFactory};
+#[cfg(feature = "opentelemetry")]
+use crate::telemetry::{self, Telemetry};
+use crate::{
+    config::PoolConfig, error::LazyPoolError, factory::Infallible, health::Health,
+    monitor::Callbacks, rate_limit::TokenBucket, reentrancy::Holders, semaphore::SemaphoreSlots,
+    stats::Usage, waiters::Waiters, window::ActivityLog, CheckoutHook, ErrorHandler, Factory, Hook,
+    Pool, PreCreateHook, Result, TryFactory, Validator,
+};
+use futures::{channel::mpsc, future::BoxFuture, lock::Mutex, FutureExt, SinkExt};
+use log::Level;
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize},
+        Arc, Mutex as SyncMutex, RwLock,
+    },
+    time::Duration,
+};
+use tokio::sync::watch;
+
+/// Log levels used by the pool for its own operations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct LogLevels {
+    pub(crate) get: Level,
+    pub(crate) release: Level,
+    pub(crate) create: Level,
+}
+
+impl Default for LogLevels {
+    fn default() -> Self {
+        Self {
+            get: Level::Debug,
+            release: Level::Debug,
+            create: Level::Debug,
+        }
+    }
+}
+
+/**
+Builder for [`Pool`] instances which need more configuration than the
+plain constructors provide.
+
+```
+# use futures::executor::block_on;
+# use lazy_pool::{PoolBuilder, SyncFactory};
+
+# struct AnyObject;
+
+let pool = block_on(async {
+    PoolBuilder::new()
+        .size(5)
+        .factory(SyncFactory::from(|| AnyObject))
+        .with_log_level(log::Level::Trace, log::Level::Trace, log::Level::Debug)
+        .build()
+        .await
+        .unwrap()
+});
+```
+*/
+pub struct PoolBuilder<T: Send> {
+    name: Option<Arc<str>>,
+    size: usize,
+    min_idle: usize,
+    shrink_to: Option<usize>,
+    factory: Option<Box<dyn TryFactory<T>>>,
+    max_create_retries: usize,
+    log_levels: LogLevels,
+    return_validator: Option<Validator<T>>,
+    pre_create_hook: Option<PreCreateHook>,
+    post_return_hook: Option<Hook<T>>,
+    post_checkout_hook: Option<CheckoutHook<T>>,
+    error_handler: Option<ErrorHandler>,
+    failure_threshold: u32,
+    activity_buffer_size: usize,
+    rate_limit: Option<f64>,
+    backpressure_delay: Duration,
+    max_idle_duration: Option<Duration>,
+    lru_eviction: bool,
+    health_check_interval: Duration,
+    max_wait: Option<Duration>,
+    #[cfg(feature = "background-factory")]
+    background_factory: Option<BackgroundFactory<T>>,
+    #[cfg(feature = "opentelemetry")]
+    telemetry_prefix: String,
+}
+
+impl<T: Send + 'static> PoolBuilder<T> {
+    /**
+    Creates a builder for a pool of 10 objects with no factory set. See
+    [`PoolConfig::default`] for the rest of the defaults.
+    */
+    pub fn new() -> Self {
+        let config = PoolConfig::default();
+        Self {
+            name: None,
+            size: config.size,
+            min_idle: config.min_idle,
+            shrink_to: None,
+            factory: None,
+            max_create_retries: config.max_create_retries,
+            log_levels: LogLevels {
+                get: config.get_log_level,
+                release: config.release_log_level,
+                create: config.create_log_level,
+            },
+            return_validator: None,
+            pre_create_hook: None,
+            post_return_hook: None,
+            post_checkout_hook: None,
+            error_handler: None,
+            failure_threshold: 5,
+            activity_buffer_size: 10_000,
+            rate_limit: None,
+            backpressure_delay: Duration::from_millis(100),
+            max_idle_duration: None,
+            lru_eviction: false,
+            health_check_interval: Duration::from_secs(30),
+            max_wait: config.max_wait,
+            #[cfg(feature = "background-factory")]
+            background_factory: None,
+            #[cfg(feature = "opentelemetry")]
+            telemetry_prefix: String::from(telemetry::DEFAULT_PREFIX),
+        }
+    }
+
+    /// Applies all settings from `config`.
+    pub fn with_config(mut self, config: PoolConfig) -> Self {
+        self.max_wait = config.max_wait;
+        self.size(config.size)
+            .min_idle(config.min_idle)
+            .with_max_create_retries(config.max_create_retries)
+            .with_log_level(
+                config.get_log_level,
+                config.release_log_level,
+                config.create_log_level,
+            )
+    }
+
+    /// Sets a name which identifies the pool in errors.
+    pub fn name(mut self, name: impl Into<Arc<str>>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Sets the maximum number of objects the pool will hold.
+    pub fn size(mut self, size: usize) -> Self {
+        self.size = size;
+        self
+    }
+
+    /**
+    Sets the number of idle objects the pool tries to keep around. They are
+    created when the pool is built and topped up after checkouts, as long
+    as there are free slots. Must not exceed the pool size.
+    */
+    pub fn min_idle(mut self, min_idle: usize) -> Self {
+        self.min_idle = min_idle;
+        self
+    }
+
+    /**
+    Lets the pool grow up to `max` objects under load and shrink back to
+    `min` objects once the load goes away. Objects are dropped when they
+    are released while the pool holds more than `min` objects and at least
+    `min` of them are idle. Overrides [`PoolBuilder::size`].
+    */
+    pub fn with_size_limit(mut self, min: usize, max: usize) -> Self {
+        self.size = max;
+        self.shrink_to = Some(min);
+        self
+    }
+
+    /// Sets the factory used to create objects. Required.
+    pub fn factory<F>(self, factory: F) -> Self
+    where
+        F: Factory<T> + 'static,
+    {
+        self.try_factory(Infallible(factory))
+    }
+
+    /// Sets a fallible factory used to create objects.
+    pub fn try_factory<F>(mut self, factory: F) -> Self
+    where
+        F: TryFactory<T> + 'static,
+    {
+        self.factory = Some(Box::new(factory));
+        #[cfg(feature = "background-factory")]
+        {
+            self.background_factory = None;
+        }
+        self
+    }
+
+    /**
+    Sets a factory whose futures don't borrow it, so that
+    [`Pool::get_nonblocking_factory`] can run them in tasks spawned on the
+    current Tokio runtime, several at a time. Other checkouts use it like a
+    factory set through [`PoolBuilder::factory`]. Requires the
+    `background-factory` feature.
+
+    ```
+    # use futures_timer::Delay;
+    # use lazy_pool::Pool;
+    # use std::time::Duration;
+
+    # struct Connection;
+
+    # #[tokio::main]
+    # async fn main() {
+    let pool = Pool::builder()
+        .size(3)
+        .background_factory(|| async {
+            Delay::new(Duration::from_millis(50)).await;
+            Connection
+        })
+        .build()
+        .await
+        .unwrap();
+    // The three connections are created at the same time.
+    let (a, b, c) = futures::join!(
+        pool.get_nonblocking_factory(),
+        pool.get_nonblocking_factory(),
+        pool.get_nonblocking_factory(),
+    );
+    # drop((a, b, c));
+    # }
+    ```
+    */
+    #[cfg(feature = "background-factory")]
+    pub fn background_factory<F, Fut>(self, factory: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = T> + Send + 'static,
+    {
+        let factory: BackgroundFactory<T> = Arc::new(move || factory().boxed());
+        let mut builder = self.factory(Background(factory.clone()));
+        builder.background_factory = Some(factory);
+        builder
+    }
+
+    /**
+    Sets how many times creating an object is retried when the created
+    object is rejected, e.g. by [`Pool::filter_map`]. Defaults to 3.
+    */
+    pub fn with_max_create_retries(mut self, max_create_retries: usize) -> Self {
+        self.max_create_retries = max_create_retries;
+        self
+    }
+
+    /**
+    Sets the levels at which checkouts, releases and object creation are
+    logged. All of them default to [`log::Level::Debug`].
+    */
+    pub fn with_log_level(
+        mut self,
+        get_level: Level,
+        release_level: Level,
+        create_level: Level,
+    ) -> Self {
+        self.log_levels = LogLevels {
+            get: get_level,
+            release: release_level,
+            create: create_level,
+        };
+        self
+    }
+
+    /**
+    Sets a validator which is run when an object is released. Objects for
+    which it returns `false` are treated as tainted and replaced instead of
+    being put back in the pool.
+    */
+    pub fn with_return_validator<V>(mut self, validator: V) -> Self
+    where
+        V: Fn(&T) -> bool + Send + Sync + 'static,
+    {
+        self.return_validator = Some(Arc::new(validator));
+        self
+    }
+
+    /**
+    Sets a hook which runs when an object is released back into the pool,
+    after it passed the return validator, e.g. to flush buffers or record
+    metrics about the object. Not run for objects which are dropped.
+    */
+    pub fn with_post_return_hook<H>(mut self, hook: H) -> Self
+    where
+        H: Fn(&T) + Send + Sync + 'static,
+    {
+        self.post_return_hook = Some(Arc::new(hook));
+        self
+    }
+
+    /**
+    Sets a hook which runs every time an object is checked out, whether it
+    was just created or reused, before it is handed to the caller. Useful
+    for resetting per-use state the pool can't clean up itself.
+    */
+    pub fn with_post_checkout_hook<H>(self, hook: H) -> Self
+    where
+        H: Fn(&T) + Send + Sync + 'static,
+    {
+        self.with_async_post_checkout_hook(move |item| {
+            hook(item);
+            async {}.boxed()
+        })
+    }
+
+    /**
+    Same as [`PoolBuilder::with_post_checkout_hook`] for asynchronous hooks,
+    which return a boxed future so that it can borrow the object. While the
+    hook runs, checkouts wait; [`Pool::try_get`] gives up instead.
+    */
+    pub fn with_async_post_checkout_hook<H>(mut self, hook: H) -> Self
+    where
+        H: for<'a> Fn(&'a T) -> BoxFuture<'a, ()> + Send + Sync + 'static,
+    {
+        self.post_checkout_hook = Some(Arc::new(hook));
+        self
+    }
+
+    /**
+    Sets a hook which runs every time right before the factory is asked for
+    a new object, e.g. to log an audit event about a new connection being
+    established. Not run for objects which are reused.
+    */
+    pub fn with_pre_create_hook<H>(self, hook: H) -> Self
+    where
+        H: Fn() + Send + Sync + 'static,
+    {
+        self.with_async_pre_create_hook(move || {
+            hook();
+            async {}
+        })
+    }
+
+    /// Same as [`PoolBuilder::with_pre_create_hook`] for asynchronous hooks.
+    pub fn with_async_pre_create_hook<H, Fut>(mut self, hook: H) -> Self
+    where
+        H: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.pre_create_hook = Some(Arc::new(move || hook().boxed()));
+        self
+    }
+
+    /**
+    Passes errors the pool otherwise only logs to `handler`, for visibility
+    into failing connections without subscribing to [`Pool::monitor`]:
+    factory failures of checkouts which then keep waiting, objects evicted
+    by [`Pool::check_health`] and failed releases. The handler is called
+    from the pool's tasks and must not block, e.g. it may log the error or
+    hand it over to a bounded channel.
+
+    ```
+    # use futures::executor::block_on;
+    # use lazy_pool::{Pool, SyncFactory};
+
+    # struct AnyObject;
+
+    let pool = block_on(
+        Pool::builder()
+            .factory(SyncFactory::from(|| AnyObject))
+            .with_connection_error_handler(|err| log::error!("connection error: {err}"))
+            .build(),
+    )
+    .unwrap();
+    ```
+    */
+    pub fn with_connection_error_handler<H>(mut self, handler: H) -> Self
+    where
+        H: Fn(LazyPoolError) + Send + Sync + 'static,
+    {
+        self.error_handler = Some(Arc::new(handler));
+        self
+    }
+
+    /**
+    Sets after how many consecutive factory failures the pool reports
+    itself as unhealthy through [`Pool::is_healthy`]. Defaults to 5.
+    */
+    pub fn with_failure_threshold(mut self, failure_threshold: u32) -> Self {
+        self.failure_threshold = failure_threshold;
+        self
+    }
+
+    /**
+    Sets how many of the most recent checkouts and creations are kept for
+    [`Pool::statistics_window`]. Defaults to 10,000.
+    */
+    pub fn with_activity_buffer_size(mut self, activity_buffer_size: usize) -> Self {
+        self.activity_buffer_size = activity_buffer_size;
+        self
+    }
+
+    /**
+    Limits checkouts to `rps` per second, spread out evenly, e.g. when the
+    pooled objects talk to a rate limited API. Checkouts wait for the rate
+    limit before waiting for an object. Must be positive.
+    */
+    pub fn with_rate_limit(mut self, rps: f64) -> Self {
+        self.rate_limit = Some(rps);
+        self
+    }
+
+    /**
+    Sets how long [`Pool::get_with_backpressure_signal`] waits before a
+    checkout while the backpressure signal is raised. Defaults to 100ms.
+    */
+    pub fn with_backpressure_delay(mut self, delay: Duration) -> Self {
+        self.backpressure_delay = delay;
+        self
+    }
+
+    /**
+    Sets how long objects may stay idle before [`Pool::evict_idle`] drops
+    them. Can be changed later through [`Pool::set_max_idle_duration`].
+    Without it, idle objects are kept indefinitely.
+    */
+    pub fn with_max_idle_duration(mut self, duration: Duration) -> Self {
+        self.max_idle_duration = Some(duration);
+        self
+    }
+
+    /**
+    Makes [`Pool::evict_oldest_n`] drop the objects which were returned to
+    the pool the longest time ago instead of those created the longest time
+    ago, evicting the least recently used objects first.
+    */
+    pub fn with_idle_lru_eviction(mut self) -> Self {
+        self.lru_eviction = true;
+        self
+    }
+
+    /**
+    Sets how often [`Pool::check_health`] checks the idle objects. Can be
+    changed later through [`Pool::set_health_check_interval`]. Defaults to
+    30 seconds.
+    */
+    pub fn with_health_check_interval(mut self, interval: Duration) -> Self {
+        self.health_check_interval = interval;
+        self
+    }
+
+    /**
+    Sets how long [`Pool::get_with_max_wait`] waits for an object before
+    failing. Without it, it waits as long as [`Pool::get`] does.
+    */
+    pub fn with_max_wait(mut self, max_wait: Duration) -> Self {
+        self.max_wait = Some(max_wait);
+        self
+    }
+
+    /**
+    Sets the prefix of the names of the OpenTelemetry spans and metrics the
+    pool emits through the global providers, e.g. `{prefix}.pool.get` spans
+    and `{prefix}.pool.checkouts` counters. Defaults to `lazy_pool`.
+    */
+    #[cfg(feature = "opentelemetry")]
+    pub fn with_telemetry_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.telemetry_prefix = prefix.into();
+        self
+    }
+
+    /**
+    Builds the pool. Fails if no factory was provided, if the configuration
+    is invalid or if the factory fails to create the minimum idle objects.
+    */
+    pub async fn build(self) -> Result<Pool<T>> {
+        if self.min_idle > self.size {
+            return Err(LazyPoolError::InvalidConfig(
+                "min_idle must not exceed the pool size",
+            ));
+        }
+        if self.shrink_to.is_some_and(|min| min > self.size) {
+            return Err(LazyPoolError::InvalidConfig(
+                "minimum size must not exceed the maximum size",
+            ));
+        }
+        if self
+            .rate_limit
+            .is_some_and(|rps| rps.is_nan() || rps <= 0.0)
+        {
+            return Err(LazyPoolError::InvalidConfig("rate limit must be positive"));
+        }
+        let factory = self.factory.ok_or(LazyPoolError::MissingFactory)?;
+        let (mut create_sender, create_receiver) = mpsc::unbounded();
+        let (return_sender, return_receiver) = mpsc::unbounded();
+        for _ in 0..self.size {
+            create_sender.send(()).await?;
+        }
+        let usage = Arc::new(Usage::new(self.size));
+        let pool = Pool {
+            name: self.name,
+            create_sender,
+            return_sender,
+            create_receiver: Arc::new(Mutex::new(create_receiver)),
+            return_receiver: Arc::new(Mutex::new(return_receiver)),
+            factory: Arc::new(Mutex::new(factory)),
+            max_create_retries: self.max_create_retries,
+            checkout_counter: Arc::new(AtomicU64::new(0)),
+            callbacks: Arc::new(Callbacks::default()),
+            log_levels: self.log_levels,
+            #[cfg(feature = "opentelemetry")]
+            telemetry: Arc::new(Telemetry::new(&self.telemetry_prefix, &usage)),
+            usage,
+            return_validator: self.return_validator,
+            pre_create_hook: self.pre_create_hook,
+            post_return_hook: self.post_return_hook,
+            post_checkout_hook: self.post_checkout_hook,
+            min_idle: self.min_idle,
+            shrink_to: self.shrink_to,
+            overflow_count: Arc::new(AtomicUsize::new(0)),
+            health: Arc::new(Health::new(self.failure_threshold)),
+            waiters: Arc::new(Waiters::new()),
+            activity: Arc::new(ActivityLog::new(self.activity_buffer_size)),
+            rate_limiter: self
+                .rate_limit
+                .map(|rps| Arc::new(SyncMutex::new(TokenBucket::new(rps)))),
+            semaphore_slots: Arc::new(SemaphoreSlots::new(self.size)),
+            holders: Arc::new(Holders::default()),
+            max_idle_duration: Arc::new(RwLock::new(self.max_idle_duration)),
+            health_check_interval: Arc::new(watch::channel(self.health_check_interval).0),
+            max_wait: self.max_wait,
+            error_handler: self.error_handler,
+            checked_out: Arc::default(),
+            backpressure_delay: self.backpressure_delay,
+            lru_eviction: self.lru_eviction,
+            #[cfg(feature = "background-factory")]
+            background_factory: self.background_factory,
+        };
+        pool.warm(self.min_idle).await?;
+        Ok(pool)
+    }
+}
+
+impl<T: Send + 'static> Default for PoolBuilder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,48 @@
+use std::{future::Future, sync::Arc};
+use tokio::sync::watch;
+
+tokio::task_local! {
+    static CURRENT: CancellationToken;
+}
+
+/**
+Signals the cancellation of a Tokio task to the checkouts it makes through
+[`crate::Pool::get_scoped_to_tokio_context`]. The token is made current
+for a task by running the task's future through
+[`CancellationToken::scope`]. Clones share the cancellation.
+*/
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<watch::Sender<bool>>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.send_replace(true);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        *self.cancelled.subscribe().borrow()
+    }
+
+    /// Resolves once the token is cancelled.
+    pub async fn cancelled(&self) {
+        let mut cancelled = self.cancelled.subscribe();
+        // The sender lives as long as `self`, so waiting can't fail.
+        let _ = cancelled.wait_for(|cancelled| *cancelled).await;
+    }
+
+    /// Runs `f` with this token as the current task's token.
+    pub fn scope<F: Future>(&self, f: F) -> impl Future<Output = F::Output> {
+        CURRENT.scope(self.clone(), f)
+    }
+
+    /// The token of the current task, if it runs in a [`CancellationToken::scope`].
+    pub(crate) fn current() -> Option<Self> {
+        CURRENT.try_with(Clone::clone).ok()
+    }
+}
@@ -0,0 +1,39 @@
+use log::Level;
+use std::time::Duration;
+
+/**
+Plain configuration for a [`crate::Pool`], for cases where the settings
+come from somewhere else, e.g. a configuration file. Use it through
+[`crate::Pool::new_with_config`] or [`crate::PoolBuilder::with_config`].
+*/
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PoolConfig {
+    /// Maximum number of objects the pool will hold.
+    pub size: usize,
+    /// Number of idle objects the pool tries to keep around.
+    pub min_idle: usize,
+    /// How many times creating an object is retried when it is rejected.
+    pub max_create_retries: usize,
+    /// Level at which checkouts are logged.
+    pub get_log_level: Level,
+    /// Level at which releases are logged.
+    pub release_log_level: Level,
+    /// Level at which object creation is logged.
+    pub create_log_level: Level,
+    /// How long [`crate::Pool::get_with_max_wait`] waits for an object.
+    pub max_wait: Option<Duration>,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            size: 10,
+            min_idle: 0,
+            max_create_retries: 3,
+            get_log_level: Level::Debug,
+            release_log_level: Level::Debug,
+            create_log_level: Level::Debug,
+            max_wait: None,
+        }
+    }
+}
@@ -0,0 +1,24 @@
+use std::time::{Duration, Instant};
+
+/**
+Point in time by which a checkout must complete. Either an absolute
+[`Instant`] or a [`Duration`] relative to the moment the checkout starts,
+so both can be passed wherever an `impl Into<Deadline>` is accepted.
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Deadline {
+    At(Instant),
+    After(Duration),
+}
+
+impl From<Instant> for Deadline {
+    fn from(instant: Instant) -> Self {
+        Deadline::At(instant)
+    }
+}
+
+impl From<Duration> for Deadline {
+    fn from(duration: Duration) -> Self {
+        Deadline::After(duration)
+    }
+}
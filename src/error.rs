@@ -1,13 +1,80 @@
-use std::result::Result as StdResult;
+use crate::FactoryError;
 use futures::channel::mpsc::SendError;
+use std::{error::Error, result::Result as StdResult, sync::Arc};
 use thiserror::Error;
+use uuid::Uuid;
+
+/// Identifies the pool and object an error relates to, where known.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PoolContext {
+    pub pool_name: Option<Arc<str>>,
+    pub object_id: Option<Uuid>,
+}
+
+impl PoolContext {
+    fn prefix(&self) -> String {
+        match &self.pool_name {
+            Some(name) => format!("[{name}] "),
+            None => String::new(),
+        }
+    }
+
+    fn suffix(&self) -> String {
+        match self.object_id {
+            Some(id) => format!(" (id={id})"),
+            None => String::new(),
+        }
+    }
+}
 
 #[derive(Error, Debug)]
 pub enum LazyPoolError {
-    #[error("failed to release object")]
-    Release,
+    #[error("{}failed to release object{}", .context.prefix(), .context.suffix())]
+    Release { context: PoolContext },
     #[error("failed to send to channel")]
-    Send(#[from] SendError)
+    Send(#[from] SendError),
+    #[error("no factory was provided to the pool builder")]
+    MissingFactory,
+    #[error("the pool's factory has been detached")]
+    NoFactory,
+    #[error("timed out waiting for an object")]
+    Timeout,
+    #[error("failed to create object")]
+    Factory(#[source] FactoryError),
+    #[error("created object was rejected")]
+    Rejected,
+    #[error("no valid object was found")]
+    ValidationFailed,
+    #[error("the pool has been shut down")]
+    Closed,
+    #[error("no object is available without waiting")]
+    Exhausted,
+    #[error("the current task already holds an object of this pool")]
+    Reentrant,
+    #[error("no shard exists at the given index")]
+    InvalidShard,
+    #[error("the caller's quota of checked out objects is used up")]
+    QuotaExceeded,
+    #[error("the error budget is exhausted")]
+    ErrorBudgetExhausted,
+    #[error("the checkout was cancelled")]
+    Cancelled,
+    #[error("the pool can't hold as many objects as were requested")]
+    InsufficientCapacity,
+    #[error("invalid pool configuration: {0}")]
+    InvalidConfig(&'static str),
 }
 
 pub type Result<T> = StdResult<T, LazyPoolError>;
+
+/**
+Error returned by [`crate::Pool::get_transactional`] when the transaction
+failed, wrapping its error along with the pool and the object it used.
+*/
+#[derive(Error, Debug)]
+#[error("{}transaction failed{}", .context.prefix(), .context.suffix())]
+pub struct PooledTransactionError {
+    pub context: PoolContext,
+    #[source]
+    pub source: Box<dyn Error + Send + Sync>,
+}
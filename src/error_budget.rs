@@ -0,0 +1,46 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+/**
+The number of failed checkouts a caller can afford, as in an SLO error
+budget, for checkouts made through [`crate::Pool::get_with_error_budget`].
+Each failure spends one unit; once the budget is spent, checkouts fail
+fast without touching the pool. Clones share the remaining budget.
+*/
+#[derive(Clone, Debug)]
+pub struct ErrorBudget {
+    remaining: Arc<AtomicU64>,
+}
+
+impl ErrorBudget {
+    pub fn new(budget: u64) -> Self {
+        Self {
+            remaining: Arc::new(AtomicU64::new(budget)),
+        }
+    }
+
+    /// Number of failures left before the budget is exhausted.
+    pub fn remaining(&self) -> u64 {
+        self.remaining.load(Ordering::SeqCst)
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    /// Refills the budget, e.g. at the start of a new SLO window.
+    pub fn reset(&self, budget: u64) {
+        self.remaining.store(budget, Ordering::SeqCst);
+    }
+
+    pub(crate) fn spend(&self) {
+        // Fails once the budget is spent, which leaves it at zero.
+        let _ = self
+            .remaining
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |remaining| {
+                remaining.checked_sub(1)
+            });
+    }
+}
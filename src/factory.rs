@@ -1,4 +1,17 @@
-use std::future::{ready, Future};
+use crate::error::LazyPoolError;
+#[cfg(feature = "background-factory")]
+use futures::future::BoxFuture;
+use futures::{lock::Mutex, FutureExt};
+use log::warn;
+use std::{
+    error::Error,
+    future::{ready, Future},
+    result::Result as StdResult,
+    sync::Arc,
+};
+
+/// Error returned by a [`TryFactory`] which failed to create an object.
+pub type FactoryError = Box<dyn Error + Send + Sync>;
 
 /** The factory trait is used to populate the Pool when items are
 created and replaced. There is a default implementation of factory
@@ -37,8 +50,85 @@ where
     fn produce(&mut self) -> Box<dyn Future<Output = T> + Unpin + Send + '_>;
 }
 
+/** Fallible counterpart of [`Factory`], for objects whose creation can
+fail, such as network connections.
+
+```
+use std::future::Future;
+use lazy_pool::{FactoryError, TryFactory};
+
+struct Connection;
+
+struct ConnectionFactory {
+    address: String,
+}
+
+impl ConnectionFactory {
+    async fn connect(&self) -> Result<Connection, FactoryError> {
+        if self.address.is_empty() {
+            return Err("no address configured".into());
+        }
+        Ok(Connection)
+    }
+}
+
+impl TryFactory<Connection> for ConnectionFactory {
+    fn try_produce(
+        &mut self,
+    ) -> Box<dyn Future<Output = Result<Connection, FactoryError>> + Send + Unpin + '_> {
+        Box::new(Box::pin(self.connect()))
+    }
+}
+```
+*/
+pub trait TryFactory<T>: Send
+where
+    T: Send,
+{
+    fn try_produce(
+        &mut self,
+    ) -> Box<dyn Future<Output = StdResult<T, FactoryError>> + Unpin + Send + '_>;
+}
+
+/// Adapts an infallible [`Factory`] into a [`TryFactory`].
+pub(crate) struct Infallible<F>(pub(crate) F);
+
+impl<T, F> TryFactory<T> for Infallible<F>
+where
+    T: Send + 'static,
+    F: Factory<T>,
+{
+    fn try_produce(
+        &mut self,
+    ) -> Box<dyn Future<Output = StdResult<T, FactoryError>> + Unpin + Send + '_> {
+        Box::new(self.0.produce().map(Ok))
+    }
+}
+
+/**
+Factory for objects created by a synchronous closure. The closure may keep
+state between calls, e.g. a counter for generating ids, as the pool never
+calls its factory concurrently.
+
+```
+# use futures::executor::block_on;
+# use lazy_pool::{Pool, SyncFactory};
+
+block_on(async {
+    let mut next_id = 0;
+    let factory = SyncFactory::from(move || {
+        next_id += 1;
+        next_id
+    });
+    let pool = Pool::new_with_factory(2, factory).await.unwrap();
+    let first = pool.get().await;
+    let second = pool.get().await;
+    assert_eq!((1, 2), (*first, *second));
+});
+```
+*/
 pub struct SyncFactory<T> {
-    func: Box<dyn Fn() -> T + Send + Sync>,
+    func: Box<dyn FnMut() -> T + Send>,
 }
 
 impl<T> Factory<T> for SyncFactory<T>
@@ -52,7 +142,7 @@ where
 
 impl<C, T> From<C> for SyncFactory<T>
 where
-    C: Fn() -> T + Send + Sync + 'static,
+    C: FnMut() -> T + Send + 'static,
 {
     fn from(func: C) -> Self {
         Self {
@@ -60,3 +150,109 @@ where
         }
     }
 }
+
+/// Factory creating objects independently of the pool, so they can be created concurrently.
+#[cfg(feature = "background-factory")]
+pub(crate) type BackgroundFactory<T> = Arc<dyn Fn() -> BoxFuture<'static, T> + Send + Sync>;
+
+/// Lets checkouts other than [`crate::Pool::get_nonblocking_factory`] use a background factory.
+#[cfg(feature = "background-factory")]
+pub(crate) struct Background<T>(pub(crate) BackgroundFactory<T>);
+
+#[cfg(feature = "background-factory")]
+impl<T> Factory<T> for Background<T>
+where
+    T: Send + 'static,
+{
+    fn produce(&mut self) -> Box<dyn Future<Output = T> + Unpin + Send + '_> {
+        Box::new((self.0)())
+    }
+}
+
+/// Stands in for the factory of a pool whose factory has been detached.
+pub(crate) struct NoFactory;
+
+impl<T> TryFactory<T> for NoFactory
+where
+    T: Send + 'static,
+{
+    fn try_produce(
+        &mut self,
+    ) -> Box<dyn Future<Output = StdResult<T, FactoryError>> + Unpin + Send + '_> {
+        Box::new(ready(Err(LazyPoolError::NoFactory.into())))
+    }
+}
+
+/// Produces objects through another pool's factory and maps them.
+pub(crate) struct MapFactory<T, F> {
+    inner: Arc<Mutex<Box<dyn TryFactory<T>>>>,
+    map: F,
+}
+
+impl<T, F> MapFactory<T, F> {
+    pub(crate) fn new(inner: Arc<Mutex<Box<dyn TryFactory<T>>>>, map: F) -> Self {
+        Self { inner, map }
+    }
+}
+
+impl<T, U, F> TryFactory<U> for MapFactory<T, F>
+where
+    T: Send + 'static,
+    U: Send,
+    F: Fn(T) -> U + Send + Sync,
+{
+    fn try_produce(
+        &mut self,
+    ) -> Box<dyn Future<Output = StdResult<U, FactoryError>> + Unpin + Send + '_> {
+        Box::new(Box::pin(async move {
+            let item = self.inner.lock().await.try_produce().await?;
+            Ok((self.map)(item))
+        }))
+    }
+}
+
+/**
+Produces objects through another pool's factory, discarding and recreating
+the ones which are rejected by the mapping, up to `max_retries` times.
+*/
+pub(crate) struct FilterMapFactory<T, F> {
+    inner: Arc<Mutex<Box<dyn TryFactory<T>>>>,
+    filter_map: F,
+    max_retries: usize,
+}
+
+impl<T, F> FilterMapFactory<T, F> {
+    pub(crate) fn new(
+        inner: Arc<Mutex<Box<dyn TryFactory<T>>>>,
+        filter_map: F,
+        max_retries: usize,
+    ) -> Self {
+        Self {
+            inner,
+            filter_map,
+            max_retries,
+        }
+    }
+}
+
+impl<T, U, F> TryFactory<U> for FilterMapFactory<T, F>
+where
+    T: Send + 'static,
+    U: Send,
+    F: Fn(T) -> Option<U> + Send + Sync,
+{
+    fn try_produce(
+        &mut self,
+    ) -> Box<dyn Future<Output = StdResult<U, FactoryError>> + Unpin + Send + '_> {
+        Box::new(Box::pin(async move {
+            for attempt in 0..=self.max_retries {
+                let item = self.inner.lock().await.try_produce().await?;
+                match (self.filter_map)(item) {
+                    Some(mapped) => return Ok(mapped),
+                    None => warn!("created object was rejected (attempt {})", attempt + 1),
+                }
+            }
+            Err(LazyPoolError::Rejected.into())
+        }))
+    }
+}
@@ -0,0 +1,54 @@
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+
+/// Health of a pool as reported by [`crate::Pool::health_status`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum HealthStatus {
+    /// The factory has been creating objects successfully.
+    Healthy { idle: usize },
+    /// The factory failed the last `recent_failures` times it was used.
+    Degraded { recent_failures: u32 },
+}
+
+/// Tracks consecutive factory failures.
+pub(crate) struct Health {
+    created: AtomicU64,
+    recent_failures: AtomicU32,
+    failure_threshold: u32,
+}
+
+impl Health {
+    pub(crate) fn new(failure_threshold: u32) -> Self {
+        Self {
+            created: AtomicU64::new(0),
+            recent_failures: AtomicU32::new(0),
+            failure_threshold,
+        }
+    }
+
+    pub(crate) fn failure_threshold(&self) -> u32 {
+        self.failure_threshold
+    }
+
+    pub(crate) fn created(&self) {
+        self.created.fetch_add(1, Ordering::SeqCst);
+        self.recent_failures.store(0, Ordering::SeqCst);
+    }
+
+    pub(crate) fn failed(&self) {
+        self.recent_failures.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Number of objects the factory created successfully.
+    pub(crate) fn total_created(&self) -> u64 {
+        self.created.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn recent_failures(&self) -> u32 {
+        self.recent_failures.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn is_healthy(&self) -> bool {
+        self.recent_failures() < self.failure_threshold
+    }
+}
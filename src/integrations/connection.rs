@@ -0,0 +1,76 @@
+//! Factories creating connections from URLs, e.g. `tcp://localhost:6379`.
+
+use crate::{error::LazyPoolError, FactoryError, Result, TryFactory};
+use futures::FutureExt;
+use std::{future::Future, result::Result as StdResult};
+use tokio::net::TcpStream;
+
+/**
+Creates [`TcpStream`]s connected to the address of a `tcp://host:port` URL.
+Failed connections are reported as factory errors, so checkouts retry them.
+
+```no_run
+# use lazy_pool::{integrations::connection::{TcpConnectionFactory, TcpHealthCheck}, Pool};
+
+# #[tokio::main]
+# async fn main() {
+let pool = Pool::builder()
+    .try_factory(TcpConnectionFactory::from_url("tcp://localhost:6379").unwrap())
+    .with_return_validator(TcpHealthCheck::is_connected)
+    .build()
+    .await
+    .unwrap();
+# }
+```
+*/
+pub struct TcpConnectionFactory {
+    address: String,
+}
+
+impl TcpConnectionFactory {
+    /// Fails with [`LazyPoolError::InvalidConfig`] if `url` isn't `tcp://host:port`.
+    pub fn from_url(url: &str) -> Result<Self> {
+        let address = url
+            .strip_prefix("tcp://")
+            .filter(|address| match address.rsplit_once(':') {
+                Some((host, port)) => !host.is_empty() && port.parse::<u16>().is_ok(),
+                None => false,
+            })
+            .ok_or(LazyPoolError::InvalidConfig(
+                "expected a connection url like tcp://host:port",
+            ))?;
+        Ok(Self {
+            address: address.to_string(),
+        })
+    }
+}
+
+impl TryFactory<TcpStream> for TcpConnectionFactory {
+    fn try_produce(
+        &mut self,
+    ) -> Box<dyn Future<Output = StdResult<TcpStream, FactoryError>> + Unpin + Send + '_> {
+        Box::new(Box::pin(async move {
+            Ok(TcpStream::connect(self.address.as_str()).await?)
+        }))
+    }
+}
+
+/// Checks whether pooled [`TcpStream`]s are still connected.
+pub struct TcpHealthCheck;
+
+impl TcpHealthCheck {
+    /**
+    Peeks at the stream without waiting or consuming any data. A stream
+    whose peer closed the connection reads as empty, and one which broke
+    fails to read; both are reported as disconnected. Meant to be used as
+    a return validator, see [`crate::PoolBuilder::with_return_validator`].
+    */
+    pub fn is_connected(stream: &TcpStream) -> bool {
+        let mut buffer = [0; 1];
+        match stream.peek(&mut buffer).now_or_never() {
+            None => true,
+            Some(Ok(read)) => read > 0,
+            Some(Err(_)) => false,
+        }
+    }
+}
@@ -0,0 +1,4 @@
+//! Factories for commonly pooled objects, each behind its own feature.
+
+#[cfg(feature = "tcp")]
+pub mod connection;
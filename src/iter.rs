@@ -0,0 +1,36 @@
+use crate::Pool;
+use futures::executor::block_on;
+
+/**
+Blocking iterator over all objects of a consumed pool, as returned by
+[`Pool::into_blocking_iter`].
+*/
+pub struct PoolBlockingIter<T: Send + 'static> {
+    pub(crate) pool: Pool<T>,
+    pub(crate) remaining: usize,
+}
+
+impl<T: Send + 'static> Iterator for PoolBlockingIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        Some(block_on(self.pool.take_next()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T: Send + 'static> IntoIterator for Pool<T> {
+    type Item = T;
+    type IntoIter = PoolBlockingIter<T>;
+
+    fn into_iter(self) -> PoolBlockingIter<T> {
+        self.into_blocking_iter()
+    }
+}
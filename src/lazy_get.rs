@@ -0,0 +1,33 @@
+use crate::{Pool, Pooled};
+use futures::future::{BoxFuture, FutureExt};
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/**
+Future returned by [`Pool::get_lazily`]. The checkout only starts when the
+future is first polled.
+*/
+pub struct LazyGet<'a, T: Send + 'static> {
+    pool: &'a Pool<T>,
+    get: Option<BoxFuture<'a, Pooled<T>>>,
+}
+
+impl<'a, T: Send + 'static> LazyGet<'a, T> {
+    pub(crate) fn new(pool: &'a Pool<T>) -> Self {
+        Self { pool, get: None }
+    }
+}
+
+impl<'a, T: Send + 'static> Future for LazyGet<'a, T> {
+    type Output = Pooled<T>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Pooled<T>> {
+        let pool = self.pool;
+        self.get
+            .get_or_insert_with(|| pool.get().boxed())
+            .poll_unpin(cx)
+    }
+}
@@ -0,0 +1,48 @@
+use crate::{CheckoutState, Pooled, PooledWith, Result};
+use std::time::{Duration, Instant};
+
+/**
+An object checked out for a limited time through
+[`crate::Pool::get_with_lease`]. Objects held for longer than the lease
+are tainted when released or dropped, so that they get replaced.
+*/
+pub type LeasedPooled<T> = PooledWith<T, Lease>;
+
+/// Expiry of a [`LeasedPooled`] object.
+pub struct Lease {
+    expires_at: Instant,
+}
+
+impl Lease {
+    pub(crate) fn new(lease: Duration) -> Self {
+        Self {
+            expires_at: Instant::now() + lease,
+        }
+    }
+
+    fn remaining(&self) -> Duration {
+        self.expires_at.saturating_duration_since(Instant::now())
+    }
+}
+
+impl<T: Send + 'static> CheckoutState<T> for Lease {
+    type Released = Result<()>;
+
+    fn before_release(&mut self, pooled: &mut Pooled<T>) {
+        if self.remaining().is_zero() {
+            log::warn!("lease of object {} expired", pooled.object_id());
+            pooled.tainted();
+        }
+    }
+
+    fn released(&mut self, result: Result<()>) -> Result<()> {
+        result
+    }
+}
+
+impl<T: Send> LeasedPooled<T> {
+    /// Time left before the lease expires, zero once it has.
+    pub fn lease_remaining(&self) -> Duration {
+        self.state().remaining()
+    }
+}
@@ -4,20 +4,144 @@
 //! The pool can be used in a threaded environment as well as an async environment
 //! See Pool documentation for more info
 
+mod autoscale;
+mod benchmark;
+mod budget;
+mod builder;
+#[cfg(feature = "cancellation")]
+mod cancellation;
+mod config;
+mod deadline;
 mod error;
+mod error_budget;
 mod factory;
+mod health;
+pub mod integrations;
+mod iter;
+mod lazy_get;
+mod lease;
+mod middleware;
+mod monitor;
+mod observer;
+mod pool_of_pools;
+mod pooled_as;
+mod pooled_mut;
+mod pooled_with;
+mod pooled_with_dep;
+mod profile;
+mod prometheus;
+mod quota;
+mod rate_limit;
+mod reentrancy;
+mod report;
+mod reset;
+mod return_guard;
+mod semaphore;
+mod sharded;
+mod shared;
+mod slot;
+mod stats;
+#[cfg(feature = "test-utils")]
+mod stress;
+#[cfg(feature = "opentelemetry")]
+mod telemetry;
+mod telemetry_context;
+mod usage_tracker;
+mod waiters;
+mod window;
+mod wrap;
 
-use error::LazyPoolError;
-pub use factory::{Factory, SyncFactory};
-use log::{debug, warn};
+pub use autoscale::{AutoScaleLogic, AutoScalePolicy, AutoScaleSignals};
+pub use benchmark::FactoryBenchmark;
+pub use budget::BudgetedGet;
+use builder::LogLevels;
+pub use builder::PoolBuilder;
+#[cfg(feature = "cancellation")]
+pub use cancellation::CancellationToken;
+pub use config::PoolConfig;
+pub use deadline::Deadline;
+pub use error::{LazyPoolError, PoolContext, PooledTransactionError};
+pub use error_budget::ErrorBudget;
+#[cfg(feature = "background-factory")]
+use factory::BackgroundFactory;
+pub use factory::{Factory, FactoryError, SyncFactory, TryFactory};
+use factory::{FilterMapFactory, Infallible, MapFactory, NoFactory};
+use health::Health;
+pub use health::HealthStatus;
+pub use iter::PoolBlockingIter;
+pub use lazy_get::LazyGet;
+pub use lease::{Lease, LeasedPooled};
+use log::{log, warn, Level};
+use middleware::MiddlewareObserver;
+pub use middleware::PoolMiddleware;
+use monitor::Callbacks;
+pub use monitor::{PoolEvent, PoolEventData, PoolMonitor};
+pub use observer::{CheckoutSource, PoolObserver};
+pub use pool_of_pools::PoolOfPools;
+pub use pooled_as::PooledAs;
+pub use pooled_mut::PooledMut;
+pub use pooled_with::{CheckoutState, PooledWith};
+pub use pooled_with_dep::{Dep, PooledWithDep};
+pub use profile::{CheckoutProfile, Profile, ProfiledPooled};
+use prometheus::Metrics;
+use quota::QuotaReservation;
+pub use quota::{MaxCheckoutsQuota, QuotaTracker};
+use rate_limit::TokenBucket;
+use reentrancy::{HolderGuard, Holders};
+pub use report::HealthReport;
+pub use reset::Reset;
+pub use return_guard::{ReleaseTask, ReturnGuard};
+pub use semaphore::SemaphorePooled;
+use semaphore::SemaphoreSlots;
+pub use sharded::ShardedPool;
+pub use shared::SharedPooled;
+use slot::Slot;
+pub use stats::PoolStats;
+use stats::Usage;
 use std::{
+    borrow::Borrow,
+    collections::{HashMap, HashSet, VecDeque},
+    error::Error,
+    fmt::{self, Debug, Display, Formatter},
+    hint, io,
     ops::{Deref, DerefMut},
-    sync::Arc,
+    panic::{self, AssertUnwindSafe},
+    pin::Pin,
+    result::Result as StdResult,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex as SyncMutex, RwLock,
+    },
+    task::{Context, Poll},
+    time::{Duration, Instant},
 };
+#[cfg(feature = "test-utils")]
+pub use stress::StressReport;
+#[cfg(feature = "opentelemetry")]
+use telemetry::Telemetry;
+use telemetry_context::CheckoutTelemetry;
+pub use telemetry_context::{Span, TelemetryContext};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    sync::watch,
+};
+pub use usage_tracker::{Tracked, TrackedPooled, UsageTracker};
+use uuid::Uuid;
+pub use waiters::Priority;
+use waiters::Waiters;
+pub use window::WindowedStats;
+use window::{Activity, ActivityLog};
+pub use wrap::{Wrap, WrappedPool, WrappedPooled};
 
 pub use error::Result;
 
-use futures::{channel::mpsc, lock::Mutex, select_biased, SinkExt, StreamExt};
+use futures::{
+    channel::{mpsc, oneshot},
+    future::{pending, poll_fn, ready, BoxFuture, Ready},
+    lock::Mutex,
+    select_biased, stream, Future, FutureExt, SinkExt, Stream, StreamExt,
+};
+use futures_timer::Delay;
 
 #[macro_export]
 macro_rules! get {
@@ -32,12 +156,155 @@ macro_rules! get {
     }};
 }
 
+/**
+Like [`get!`], but gives up waiting for an object once the deadline passes.
+The deadline can be anything convertible into a [`Deadline`], i.e. an
+[`Instant`] or a [`Duration`]. The macro evaluates to a [`Result`] holding
+the value of the block, which only runs if an object was obtained.
+
+```
+# use futures::executor::block_on;
+# use lazy_pool::{Pool, get_with_deadline};
+# use std::time::Duration;
+
+# struct AnyObject;
+
+block_on(async {
+    let pool = Pool::new(1, Box::new(|| AnyObject)).await.unwrap();
+    let result = get_with_deadline!(object = pool, Duration::from_millis(10) => {
+        // Do work with the object.
+    });
+    assert!(result.is_ok());
+});
+```
+*/
+#[macro_export]
+macro_rules! get_with_deadline {
+    ($item:ident = $pool:expr, $deadline:expr => $block:expr) => {{
+        let pooled = match $crate::Deadline::from($deadline) {
+            $crate::Deadline::At(instant) => $pool.get_with_deadline(instant).await,
+            $crate::Deadline::After(duration) => $pool.get_timeout(duration).await,
+        };
+        match pooled {
+            #[allow(unused_mut)]
+            Ok(mut $item) => {
+                let res = $block;
+                if let Err(err) = $item.release().await {
+                    ::log::error!("failed to release object: {err:?}");
+                }
+                Ok(res)
+            }
+            Err(err) => Err(err),
+        }
+    }};
+}
+
+#[cfg(feature = "tracing")]
+#[doc(hidden)]
+pub use tracing as __tracing;
+
+/**
+Like [`get!`], but runs the checkout and the block inside a tracing span
+named `span_name`, with the pool's name, the number of idle objects and the
+time spent waiting for the object as `pool.name`, `pool.idle` and
+`pool.wait_ms` fields. The block runs inside an async block, so `return`
+and `?` apply to that. Without the `tracing` feature this is the same as
+[`get!`].
+
+```
+# use futures::executor::block_on;
+# use lazy_pool::{Pool, get_traced};
+
+# struct AnyObject;
+
+block_on(async {
+    let pool = Pool::new(1, Box::new(|| AnyObject)).await.unwrap();
+    get_traced!(object = pool, span_name = "my_operation" => {
+        // Do work with the object.
+    });
+});
+```
+*/
+#[cfg(feature = "tracing")]
+#[macro_export]
+macro_rules! get_traced {
+    ($item:ident = $pool:expr, span_name = $name:literal => $block:expr) => {{
+        use $crate::__tracing::Instrument;
+        let pool = &$pool;
+        let span = $crate::__tracing::info_span!(
+            $name,
+            pool.name = pool.name().unwrap_or_default(),
+            pool.idle = pool.stats().idle,
+            pool.wait_ms = $crate::__tracing::field::Empty,
+        );
+        let traced_span = span.clone();
+        async move {
+            let started = ::std::time::Instant::now();
+            #[allow(unused_mut)]
+            let mut $item = pool.get().await;
+            traced_span.record("pool.wait_ms", started.elapsed().as_millis() as u64);
+            let res = $block;
+            if let Err(err) = $item.release().await {
+                ::log::error!("failed to release object: {err:?}");
+            }
+            res
+        }
+        .instrument(span)
+        .await
+    }};
+}
+
+#[cfg(not(feature = "tracing"))]
+#[macro_export]
+macro_rules! get_traced {
+    ($item:ident = $pool:expr, span_name = $name:literal => $block:expr) => {
+        $crate::get!($item = $pool => $block)
+    };
+}
+
+pub(crate) type Validator<T> = Arc<dyn Fn(&T) -> bool + Send + Sync>;
+pub(crate) type Hook<T> = Arc<dyn Fn(&T) + Send + Sync>;
+pub(crate) type PreCreateHook = Arc<dyn Fn() -> BoxFuture<'static, ()> + Send + Sync>;
+pub(crate) type ReturnCallback<T> = Box<dyn FnOnce(&T) + Send>;
+pub(crate) type ErrorHandler = Arc<dyn Fn(LazyPoolError) + Send + Sync>;
+pub(crate) type CheckoutHook<T> = Arc<dyn for<'a> Fn(&'a T) -> BoxFuture<'a, ()> + Send + Sync>;
+
 pub struct Pool<T: Send> {
-    factory: Arc<Mutex<Box<dyn Factory<T>>>>,
-    return_receiver: Arc<Mutex<mpsc::Receiver<T>>>,
-    create_receiver: Arc<Mutex<mpsc::Receiver<()>>>,
-    return_sender: mpsc::Sender<T>,
-    create_sender: mpsc::Sender<()>,
+    name: Option<Arc<str>>,
+    factory: Arc<Mutex<Box<dyn TryFactory<T>>>>,
+    max_create_retries: usize,
+    return_receiver: Arc<Mutex<mpsc::UnboundedReceiver<Slot<T>>>>,
+    create_receiver: Arc<Mutex<mpsc::UnboundedReceiver<()>>>,
+    return_sender: mpsc::UnboundedSender<Slot<T>>,
+    create_sender: mpsc::UnboundedSender<()>,
+    checkout_counter: Arc<AtomicU64>,
+    callbacks: Arc<Callbacks>,
+    log_levels: LogLevels,
+    usage: Arc<Usage>,
+    return_validator: Option<Validator<T>>,
+    pre_create_hook: Option<PreCreateHook>,
+    post_return_hook: Option<Hook<T>>,
+    post_checkout_hook: Option<CheckoutHook<T>>,
+    min_idle: usize,
+    shrink_to: Option<usize>,
+    overflow_count: Arc<AtomicUsize>,
+    health: Arc<Health>,
+    waiters: Arc<Waiters<Slot<T>>>,
+    activity: Arc<ActivityLog>,
+    rate_limiter: Option<Arc<SyncMutex<TokenBucket>>>,
+    semaphore_slots: Arc<SemaphoreSlots<T>>,
+    holders: Arc<Holders>,
+    max_idle_duration: Arc<RwLock<Option<Duration>>>,
+    health_check_interval: Arc<watch::Sender<Duration>>,
+    max_wait: Option<Duration>,
+    error_handler: Option<ErrorHandler>,
+    checked_out: Arc<SyncMutex<HashMap<Uuid, Instant>>>,
+    backpressure_delay: Duration,
+    lru_eviction: bool,
+    #[cfg(feature = "background-factory")]
+    background_factory: Option<BackgroundFactory<T>>,
+    #[cfg(feature = "opentelemetry")]
+    telemetry: Arc<Telemetry>,
 }
 
 impl<T: Send + 'static> Pool<T> {
@@ -68,6 +335,35 @@ impl<T: Send + 'static> Pool<T> {
         Self::new_with_factory(size, SyncFactory::from(factory)).await
     }
 
+    /**
+    Same as [`Pool::new`], but creates all of the pool's objects before
+    returning, so that the first checkouts do not have to wait for them.
+    */
+    pub async fn new_eager<F>(size: usize, factory: F) -> Result<Self>
+    where
+        SyncFactory<T>: From<F>,
+    {
+        let pool = Self::new(size, factory).await?;
+        pool.warm(size).await?;
+        Ok(pool)
+    }
+
+    /**
+    Same as [`Pool::new`], but keeps at least `min_idle` objects idle in the
+    pool, as long as there are free slots. See [`PoolBuilder::min_idle`].
+    */
+    pub async fn new_with_min_idle<F>(size: usize, min_idle: usize, factory: F) -> Result<Self>
+    where
+        SyncFactory<T>: From<F>,
+    {
+        PoolBuilder::new()
+            .size(size)
+            .min_idle(min_idle)
+            .factory(SyncFactory::from(factory))
+            .build()
+            .await
+    }
+
     /**
     Creating a Pool instance with a [`Factory`]
     Check [`Factory`] docs for an example of creating an async factory.
@@ -76,23 +372,159 @@ impl<T: Send + 'static> Pool<T> {
     where
         F: Factory<T> + 'static,
     {
-        let (mut create_sender, create_receiver) = mpsc::channel(size);
-        let (return_sender, return_receiver) = mpsc::channel(size);
-        for _ in 0..size {
-            create_sender.send(()).await?;
+        PoolBuilder::new().size(size).factory(factory).build().await
+    }
+
+    /**
+    Creating a Pool instance from a [`PoolConfig`]. Fails if the
+    configuration is invalid.
+
+    ```
+    # use futures::executor::block_on;
+    # use lazy_pool::{Pool, PoolConfig, SyncFactory};
+
+    # struct AnyObject;
+
+    let config = PoolConfig {
+        size: 5,
+        min_idle: 1,
+        ..PoolConfig::default()
+    };
+    let pool = block_on(Pool::new_with_config(config, SyncFactory::from(|| AnyObject))).unwrap();
+    assert_eq!(1, pool.stats().idle);
+    ```
+    */
+    pub async fn new_with_config<F>(config: PoolConfig, factory: F) -> Result<Self>
+    where
+        F: Factory<T> + 'static,
+    {
+        PoolBuilder::new()
+            .with_config(config)
+            .factory(factory)
+            .build()
+            .await
+    }
+
+    /**
+    Creating a Pool instance with a [`TryFactory`]. Objects which fail to be
+    created do not take up a slot in the pool; checkouts keep waiting until
+    an object is created or returned.
+    */
+    pub async fn new_with_try_factory<F>(size: usize, factory: F) -> Result<Self>
+    where
+        F: TryFactory<T> + 'static,
+    {
+        PoolBuilder::new()
+            .size(size)
+            .try_factory(factory)
+            .build()
+            .await
+    }
+
+    /**
+    Same as [`Pool::new`] with a size of `max_size`, seeded with `objects`,
+    e.g. connections which were already established. The objects are idle
+    in the pool and handed out in the given order before the factory
+    creates any new ones. Fails with [`LazyPoolError::InsufficientCapacity`]
+    if there are more than `max_size` objects.
+
+    ```
+    # use futures::executor::block_on;
+    # use lazy_pool::Pool;
+
+    block_on(async {
+        let seeded = vec![String::from("seeded")];
+        let pool = Pool::new_with_initial_objects(seeded, 2, || String::from("created"))
+            .await
+            .unwrap();
+        let first = pool.get().await;
+        let second = pool.get().await;
+        assert_eq!(("seeded", "created"), (first.as_str(), second.as_str()));
+    });
+    ```
+    */
+    pub async fn new_with_initial_objects<F>(
+        objects: Vec<T>,
+        max_size: usize,
+        factory: F,
+    ) -> Result<Self>
+    where
+        SyncFactory<T>: From<F>,
+    {
+        if objects.len() > max_size {
+            return Err(LazyPoolError::InsufficientCapacity);
         }
-        Ok(Pool {
-            create_sender,
-            return_sender,
-            create_receiver: Arc::new(Mutex::new(create_receiver)),
-            return_receiver: Arc::new(Mutex::new(return_receiver)),
-            factory: Arc::new(Mutex::new(Box::new(factory))),
-        })
+        let pool = Self::new(max_size, factory).await?;
+        let mut create_receiver = pool.create_receiver.lock().await;
+        for item in objects {
+            create_receiver.next().await;
+            let slot = Slot::new(item);
+            log!(pool.log_levels.create, "seeded object {}", slot.id);
+            pool.put_idle(slot).await?;
+        }
+        drop(create_receiver);
+        Ok(pool)
+    }
+
+    /**
+    Creates two pools of different objects sharing a budget of `total`
+    objects, e.g. for the stages of a pipeline. Objects checked out from
+    either pool count against the shared budget, so a checkout waits once
+    `total` objects are in use across both pools. Like with [`Pool::map`],
+    idle objects keep their slot in the pool they were returned to; taint
+    an object on release to hand its slot over to the other pool.
+
+    ```
+    # use futures::executor::block_on;
+    # use lazy_pool::{Pool, SyncFactory};
+
+    block_on(async {
+        let (producers, consumers) = Pool::pair(
+            2,
+            SyncFactory::from(|| "producer"),
+            SyncFactory::from(|| "consumer"),
+        )
+        .await
+        .unwrap();
+        let producer = producers.get().await;
+        let consumer = consumers.get().await;
+        assert!(producers.try_get().is_none());
+        producer.release().await.unwrap();
+        consumer.release().await.unwrap();
+    });
+    ```
+    */
+    pub async fn pair<U, FA, FB>(
+        total: usize,
+        factory_a: FA,
+        factory_b: FB,
+    ) -> Result<(Self, Pool<U>)>
+    where
+        U: Send + 'static,
+        FA: Factory<T> + 'static,
+        FB: Factory<U> + 'static,
+    {
+        let pool_a = Self::new_with_factory(total, factory_a).await?;
+        let pool_b = pool_a.derive(Infallible(factory_b));
+        Ok((pool_a, pool_b))
     }
 
     /**
-    To get an object out of the pool use get. This will return a future
-    so you either need to await on it or to use it in an async manner
+    Creates a [`PoolBuilder`] for configuring a pool beyond its size and
+    factory.
+    */
+    pub fn builder() -> PoolBuilder<T> {
+        PoolBuilder::new()
+    }
+
+    /**
+    To get an object out of the pool use get. Never completes once the pool
+    has been shut down through [`Pool::drain_and_shutdown`], use
+    [`Pool::get_timeout`] or [`Pool::get_with_max_wait`] to fail with
+    [`LazyPoolError::Closed`] instead.
+
+    This will return a future so you either need to await on it or to use
+    it in an async manner
 
     ```
     # use futures::executor::block_on;
@@ -112,338 +544,6624 @@ impl<T: Send + 'static> Pool<T> {
     ```
     */
     pub async fn get(&self) -> Pooled<T> {
-        debug!("getting item");
-        let object = self.next_available().await;
-        Pooled {
-            wrapped: Some(object),
-            tainted: false,
-            create_sender: self.create_sender.clone(),
-            return_sender: self.return_sender.clone(),
+        #[cfg(feature = "opentelemetry")]
+        let _span = self.telemetry.span("get");
+        let started = Instant::now();
+        log!(self.log_levels.get, "getting item");
+        self.wait_if_closed().await;
+        self.throttle().await;
+        self.checkout_next(started).await
+    }
+
+    async fn checkout_next(&self, started: Instant) -> Pooled<T> {
+        let slot = self.next_available().await;
+        let pooled = self.checkout(slot, false, started);
+        self.replenish().await;
+        self.after_checkout(pooled).await
+    }
+
+    /**
+    Runs the hook set through [`PoolBuilder::with_post_checkout_hook`]. The
+    object is already wrapped, so that it is released if the checkout is
+    cancelled while the hook runs.
+    */
+    async fn after_checkout(&self, pooled: Pooled<T>) -> Pooled<T> {
+        if let Some(hook) = &self.post_checkout_hook {
+            hook(&pooled).await;
         }
+        pooled
     }
 
-    async fn next_available(&self) -> T {
-        let mut return_receiver = self.return_receiver.lock().await;
-        let mut create_receiver = self.create_receiver.lock().await;
-        select_biased! {
-            item = return_receiver.next() => {
-                debug!("using returned object");
-                item.expect("whoops")
-            },
-            _ = create_receiver.next() => {
-                debug!("creating object");
-                self.create().await
-            }
+    /// Waits for the rate limit set through [`PoolBuilder::with_rate_limit`].
+    async fn throttle(&self) {
+        let Some(rate_limiter) = &self.rate_limiter else {
+            return;
+        };
+        let wait = rate_limiter.lock().unwrap().reserve();
+        if !wait.is_zero() {
+            log!(self.log_levels.get, "rate limited for {wait:?}");
+            Delay::new(wait).await;
+        }
+    }
+
+    /**
+    Same as [`Pool::get`], but first waits until the pool has more than
+    `idle_required` objects to spare, counting idle objects and free slots,
+    so that at least `idle_required` remain for others after the checkout.
+    Useful for keeping headroom for more critical users of the pool.
+    */
+    pub async fn get_or_wait_for_idle(&self, idle_required: usize) -> Pooled<T> {
+        let mut in_use = self.usage.subscribe_in_use();
+        // The sender is owned by the pool, which outlives this future.
+        let _ = in_use
+            .wait_for(|in_use| {
+                let capacity = self.stats().capacity;
+                capacity.saturating_sub(*in_use) > idle_required
+            })
+            .await;
+        self.get().await
+    }
+
+    /**
+    Checks out an object unless `max_in_use` or more objects are already
+    in use, in which case `None` is returned right away. Lets a subsystem
+    use at most part of a pool shared with others, independently of the
+    pool's capacity. Concurrent calls may briefly exceed the threshold, as
+    the check happens before waiting for the object.
+    */
+    pub async fn get_if_below_threshold(&self, max_in_use: usize) -> Option<Pooled<T>> {
+        if self.stats().in_use >= max_in_use {
+            log!(
+                self.log_levels.get,
+                "not getting item, {max_in_use} or more in use"
+            );
+            return None;
         }
+        Some(self.get().await)
     }
 
-    async fn create(&self) -> T {
-        self.factory.lock().await.produce().await
+    /**
+    Checks out an object for an operation which has to complete within
+    `budget_ms` milliseconds. Fails with [`LazyPoolError::Timeout`] if the
+    whole budget is spent waiting. Otherwise the returned [`BudgetedGet`]
+    tells how much of the budget is left for the operation itself.
+
+    ```
+    # use futures::executor::block_on;
+    # use lazy_pool::Pool;
+
+    # struct AnyObject;
+
+    block_on(async {
+        let pool = Pool::new(1, Box::new(|| AnyObject)).await.unwrap();
+        let object = pool.get_within_budget(100).await.unwrap();
+        assert!(object.remaining_budget_ms() <= 100);
+        object.release().await.unwrap();
+    });
+    ```
+    */
+    pub async fn get_within_budget(&self, budget_ms: u64) -> Result<BudgetedGet<T>> {
+        let started = Instant::now();
+        let budget = Duration::from_millis(budget_ms);
+        let pooled = self.get_timeout(budget).await?;
+        Ok(BudgetedGet::new(pooled, started, budget))
     }
-}
 
-pub struct Pooled<T: Send + 'static> {
-    wrapped: Option<T>,
-    tainted: bool,
-    return_sender: mpsc::Sender<T>,
-    create_sender: mpsc::Sender<()>,
-}
+    /**
+    Same as [`Pool::get`], but when the pool is exhausted the checkout
+    queues up by `priority`: released objects go to the waiting checkout
+    with the lowest priority value first, and to those with equal priority
+    in the order they started waiting. Checkouts waiting this way are
+    served before checkouts made through [`Pool::get`].
 
-impl<T: Send> Pooled<T> {
-    pub fn tainted(&mut self) {
-        self.tainted = true;
+    ```
+    # use futures::executor::block_on;
+    # use lazy_pool::Pool;
+
+    # struct AnyObject;
+
+    block_on(async {
+        let pool = Pool::new(1, Box::new(|| AnyObject)).await.unwrap();
+        let object = pool.get_with_priority(0).await;
+        object.release().await.unwrap();
+    });
+    ```
+    */
+    pub async fn get_with_priority(&self, priority: Priority) -> Pooled<T> {
+        let started = Instant::now();
+        self.wait_if_closed().await;
+        self.throttle().await;
+        if let Some(slot) = self.try_next_available() {
+            return self
+                .after_checkout(self.checkout(slot, false, started))
+                .await;
+        }
+        log!(
+            self.log_levels.get,
+            "waiting for item (priority = {priority})"
+        );
+        let mut waiter = WaiterGuard {
+            receiver: self.waiters.enqueue(priority),
+            pool: self,
+        };
+        // An object may have been returned before the waiter was queued.
+        let slot = match self.try_take_idle() {
+            Some(slot) => slot,
+            None => select_biased! {
+                served = (&mut waiter.receiver).fuse() => served.expect("waiters live as long as the pool"),
+                slot = self.create_next().fuse() => slot,
+            },
+        };
+        drop(waiter);
+        let pooled = self.checkout(slot, false, started);
+        self.replenish().await;
+        self.after_checkout(pooled).await
     }
 
-    pub async fn release(mut self) -> Result<()> {
-        debug!("releasing object (tainted = {})", self.tainted);
-        match (self.tainted, self.wrapped.take()) {
-            (_, None) => {
-                warn!("release called multiple times");
-                Ok(())
+    /// Waits for a free slot and creates an object in it.
+    async fn create_next(&self) -> Slot<T> {
+        loop {
+            let mut create_receiver = self.create_receiver.lock().await;
+            create_receiver.next().await;
+            drop(create_receiver);
+            let mut guard = CreateGuard(Some(self.create_sender.clone()));
+            match self.create().await {
+                Ok(slot) => {
+                    guard.0 = None;
+                    return slot;
+                }
+                Err(err) => {
+                    warn!("failed to create object: {err:?}");
+                    self.report_error(err);
+                }
             }
-            (true, _) => self.create_sender.send(()).await,
-            (false, Some(item)) => self.return_sender.send(item).await,
         }
-        .map_err(|_| LazyPoolError::Release)
     }
-}
 
-impl<T: Send> DerefMut for Pooled<T> {
-    fn deref_mut(&mut self) -> &mut T {
-        self.wrapped.as_mut().unwrap()
+    /**
+    Same as [`Pool::get`] but hands out the most recently returned object
+    instead of the one which has been idle the longest, since it is the most
+    likely to still be warm. Falls back to [`Pool::get`] if no object is
+    idle.
+    */
+    pub async fn get_latest(&self) -> Pooled<T> {
+        let started = Instant::now();
+        log!(self.log_levels.get, "getting latest item");
+        self.wait_if_closed().await;
+        self.throttle().await;
+        let slot = match self.take_latest_idle().await {
+            Some(slot) => slot,
+            None => self.next_available().await,
+        };
+        let pooled = self.checkout(slot, false, started);
+        self.replenish().await;
+        self.after_checkout(pooled).await
     }
-}
 
-impl<T: Send> Deref for Pooled<T> {
-    type Target = T;
+    /**
+    Checks out an object which passes `validate`. Objects failing it are
+    tainted and released, and another one is checked out, up to
+    `max_retries` times. Fails with [`LazyPoolError::ValidationFailed`] if
+    no valid object was found by then.
 
-    fn deref(&self) -> &T {
-        self.wrapped.as_ref().unwrap()
+    ```
+    # use futures::executor::block_on;
+    # use lazy_pool::Pool;
+
+    block_on(async {
+        let pool = Pool::new(1, Box::new(|| String::from("connected"))).await.unwrap();
+        let connection = pool
+            .get_with_retry(|connection| connection == "connected", 3)
+            .await
+            .unwrap();
+        connection.release().await.unwrap();
+    });
+    ```
+    */
+    pub async fn get_with_retry<F>(&self, validate: F, max_retries: usize) -> Result<Pooled<T>>
+    where
+        F: Fn(&T) -> bool,
+    {
+        for attempt in 0..=max_retries {
+            let mut pooled = self.get().await;
+            if validate(&pooled) {
+                return Ok(pooled);
+            }
+            log!(
+                self.log_levels.get,
+                "object failed validation (attempt {})",
+                attempt + 1
+            );
+            pooled.tainted();
+            pooled.release().await?;
+        }
+        Err(LazyPoolError::ValidationFailed)
     }
-}
 
-#[cfg(test)]
-mod tests {
+    /**
+    Checks out an object and applies `f` to it, up to `max_attempts` times
+    and at least once, until `f` succeeds. Objects for which `f` fails are
+    tainted, so every attempt gets a different object, e.g. a fresh
+    connection when the first one turns out to be stale. Returns the first
+    success or the last error. The future returned by `f` can't borrow the
+    object, so `f` takes what it needs from the object before returning it.
+    Objects are released before returning; failures to release them are
+    logged.
 
-    extern crate uuid;
+    ```
+    # use futures::executor::block_on;
+    # use lazy_pool::Pool;
 
-    use super::*;
+    block_on(async {
+        let pool = Pool::new(1, Box::new(|| 1)).await.unwrap();
+        let result = pool
+            .get_until_success(
+                |value| {
+                    let next = *value + 1;
+                    async move { Ok::<_, ()>(next) }
+                },
+                3,
+            )
+            .await;
+        assert_eq!(Ok(2), result);
+    });
+    ```
+    */
+    pub async fn get_until_success<F, Fut, R, E>(
+        &self,
+        f: F,
+        max_attempts: usize,
+    ) -> StdResult<R, E>
+    where
+        F: Fn(&mut T) -> Fut,
+        Fut: Future<Output = StdResult<R, E>>,
+    {
+        let mut attempt = 1;
+        loop {
+            let mut pooled = self.get().await;
+            let result = f(&mut pooled).await;
+            if result.is_err() {
+                log!(self.log_levels.get, "attempt {attempt} failed");
+                pooled.tainted();
+            }
+            if let Err(err) = pooled.release().await {
+                log::error!("failed to release object: {err:?}");
+            }
+            if result.is_ok() || attempt >= max_attempts {
+                return result;
+            }
+            attempt += 1;
+        }
+    }
 
-    use futures::{executor::block_on, select, Future, FutureExt};
-    use futures_timer::Delay;
-    use log::debug;
-    use std::{
-        collections::HashSet,
-        iter::FromIterator,
-        sync::{Arc, Mutex as SyncMutex},
-        thread,
-        time::Duration,
-    };
-    use test_log::test;
-    use tokio::task::JoinSet;
+    /**
+    Checks out an object and runs `f` with it, like a transaction: when
+    `f` succeeds, the object is released back into the pool, and when it
+    fails, the object is tainted so that it gets replaced. As with
+    [`Pool::get_until_success`], the future returned by `f` can't borrow
+    the object. Failures to release the object are logged.
 
-    #[derive(PartialEq, Debug, Clone)]
-    struct AnyObject {
-        member: String,
-    }
+    ```
+    # use futures::executor::block_on;
+    # use lazy_pool::Pool;
 
-    impl AnyObject {
-        fn new() -> Self {
-            AnyObject {
-                member: uuid::Uuid::new_v4().to_string(),
+    block_on(async {
+        let pool = Pool::new(1, Box::new(|| 1)).await.unwrap();
+        let result = pool
+            .get_transactional(|value| {
+                *value += 1;
+                async { Err::<(), _>("rolled back".into()) }
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(1, *pool.get().await);
+    });
+    ```
+    */
+    pub async fn get_transactional<F, Fut, R>(&self, f: F) -> StdResult<R, PooledTransactionError>
+    where
+        F: FnOnce(&mut T) -> Fut,
+        Fut: Future<Output = StdResult<R, Box<dyn Error + Send + Sync>>>,
+    {
+        let mut pooled = self.get().await;
+        let result = f(&mut pooled).await.map_err(|source| {
+            log!(self.log_levels.get, "transaction failed");
+            PooledTransactionError {
+                context: PoolContext {
+                    pool_name: self.name.clone(),
+                    object_id: Some(pooled.object_id()),
+                },
+                source,
             }
+        });
+        if result.is_err() {
+            pooled.tainted();
+        }
+        if let Err(err) = pooled.release().await {
+            log::error!("failed to release object: {err:?}");
         }
+        result
+    }
 
-        fn with_context(context: &str) -> Self {
-            AnyObject {
-                member: String::from(context),
+    /**
+    Checks out an object only if one can be handed out without waiting,
+    i.e. there is an idle object or a free slot and the factory as well as
+    any post checkout hook complete immediately. Returns `None` otherwise.
+    */
+    pub fn try_get(&self) -> Option<Pooled<T>> {
+        log!(self.log_levels.get, "trying to get item");
+        if self.usage.is_closed() {
+            return None;
+        }
+        if let Some(rate_limiter) = &self.rate_limiter {
+            if !rate_limiter.lock().unwrap().try_acquire() {
+                return None;
             }
         }
+        let slot = self.try_next_available()?;
+        // Objects whose post checkout hook doesn't complete right away are
+        // released again when the hook is dropped.
+        self.after_checkout(self.checkout(slot, false, Instant::now()))
+            .now_or_never()
     }
 
-    #[test(tokio::test)]
-    async fn can_get_object_from_pool() {
-        let pool = Pool::new(
-            10,
-            Box::new(|| AnyObject {
-                member: String::from("member"),
-            }),
-        )
-        .await
-        .unwrap();
-        assert_eq!(
+    /**
+    Checks out up to `n` objects which can be handed out without waiting,
+    as with [`Pool::try_get`], and returns however many that is. Useful for
+    batch processing which works with whatever capacity is available.
+    */
+    pub fn try_get_n(&self, n: usize) -> Vec<Pooled<T>> {
+        let taken: Vec<_> = (0..n).map_while(|_| self.try_get()).collect();
+        log!(self.log_levels.get, "got {} of {n} items", taken.len());
+        taken
+    }
+
+    /**
+    Checks out `n` objects, waiting for each of them as with [`Pool::get`].
+    The objects are distinct from each other, as an object is never held by
+    more than one [`Pooled`] at a time. Fails with
+    [`LazyPoolError::InsufficientCapacity`] right away if the pool can't
+    hold `n` objects, instead of waiting forever. Checkouts made this way
+    at the same time can wait for each other's objects; give them enough
+    capacity between them.
+    */
+    pub async fn get_n_distinct(&self, n: usize) -> Result<Vec<Pooled<T>>> {
+        if n > self.stats().capacity {
+            return Err(LazyPoolError::InsufficientCapacity);
+        }
+        let mut taken = Vec::with_capacity(n);
+        for _ in 0..n {
+            taken.push(self.get().await);
+        }
+        debug_assert_eq!(
+            n,
+            taken
+                .iter()
+                .map(Pooled::object_id)
+                .collect::<HashSet<_>>()
+                .len()
+        );
+        Ok(taken)
+    }
+
+    /**
+    Same as [`Pool::get`], but first calls [`Pool::try_get`] up to
+    `spin_count` times, busy-waiting in between, before suspending the task.
+    For pools of objects which are only held for very short times, where
+    suspending and waking up the task takes longer than the wait itself.
+    Spinning only pays off when objects are released from other threads.
+    */
+    pub async fn get_or_spin(&self, spin_count: usize) -> Pooled<T> {
+        for _ in 0..spin_count {
+            if let Some(pooled) = self.try_get() {
+                return pooled;
+            }
+            hint::spin_loop();
+        }
+        log!(self.log_levels.get, "no item after {spin_count} spins");
+        self.get().await
+    }
+
+    /**
+    Same as [`Pool::try_get`], wrapped in a future which resolves on its
+    first poll with either the object or [`LazyPoolError::Exhausted`]. Useful
+    in async contexts which must not be suspended.
+
+    ```
+    # use futures::executor::block_on;
+    # use lazy_pool::Pool;
+
+    # struct AnyObject;
+
+    block_on(async {
+        let pool = Pool::new(1, Box::new(|| AnyObject)).await.unwrap();
+        let object = pool.get_if_ready().await.unwrap();
+        assert!(pool.get_if_ready().await.is_err());
+        object.release().await.unwrap();
+    });
+    ```
+    */
+    pub fn get_if_ready(&self) -> Ready<Result<Pooled<T>>> {
+        ready(self.try_get().ok_or(LazyPoolError::Exhausted))
+    }
+
+    /**
+    Checks out an object, applies `f` to it and releases it again. Handy
+    for updating state held by pooled objects. If `f` panics the object is
+    tainted and released before the panic is resumed.
+
+    ```
+    # use futures::executor::block_on;
+    # use lazy_pool::{Pool, get};
+
+    block_on(async {
+        let pool = Pool::new(1, Box::new(|| String::from("token"))).await.unwrap();
+        pool.exchange(|token| token.push_str("-refreshed")).await.unwrap();
+        assert_eq!("token-refreshed", get!(token = pool => token.clone()));
+    });
+    ```
+    */
+    pub async fn exchange<F>(&self, f: F) -> Result<()>
+    where
+        F: FnOnce(&mut T),
+    {
+        let mut pooled = self.get().await;
+        let outcome = panic::catch_unwind(AssertUnwindSafe(|| f(&mut pooled)));
+        if outcome.is_err() {
+            pooled.tainted();
+        }
+        let result = pooled.release().await;
+        if let Err(payload) = outcome {
+            panic::resume_unwind(payload);
+        }
+        result
+    }
+
+    /**
+    Checks out an object, awaits the future returned by `f` and releases
+    the object before resolving with the future's output. The future can't
+    borrow the object, so `f` has to copy whatever it needs out of it.
+
+    ```
+    # use futures::executor::block_on;
+    # use lazy_pool::Pool;
+    # use std::future::Future;
+
+    struct Connection {
+        url: String,
+    }
+
+    impl Connection {
+        fn query(&mut self, sql: &str) -> impl Future<Output = String> {
+            let request = format!("{} <- {sql}", self.url);
+            async move { request }
+        }
+    }
+
+    block_on(async {
+        let pool = Pool::new(1, Box::new(|| Connection { url: "db".into() }))
+            .await
+            .unwrap();
+        let rows = pool
+            .get_async_with(|connection| connection.query("SELECT 1"))
+            .await
+            .unwrap();
+        assert_eq!("db <- SELECT 1", rows);
+    });
+    ```
+    */
+    pub async fn get_async_with<F, Fut, R>(&self, f: F) -> Result<R>
+    where
+        F: FnOnce(&mut T) -> Fut,
+        Fut: Future<Output = R>,
+    {
+        let mut pooled = self.get().await;
+        let output = f(&mut pooled).await;
+        pooled.release().await?;
+        Ok(output)
+    }
+
+    /**
+    Checks out an object and hands ownership of it to `f`, which resolves
+    with the object to put back in its place along with an output. The
+    replacement is released like any other object and the output returned.
+
+    ```
+    # use futures::executor::block_on;
+    # use lazy_pool::{Pool, get};
+
+    block_on(async {
+        let pool = Pool::new(1, Box::new(|| vec![1])).await.unwrap();
+        let old_len = pool
+            .take_and_replace(|values| async move { (vec![1, 2], values.len()) })
+            .await
+            .unwrap();
+        assert_eq!(1, old_len);
+        assert_eq!(2, get!(values = pool => values.len()));
+    });
+    ```
+    */
+    pub async fn take_and_replace<F, Fut, U>(&self, f: F) -> Result<U>
+    where
+        F: FnOnce(T) -> Fut,
+        Fut: Future<Output = (T, U)>,
+    {
+        let mut pooled = self.get().await;
+        let mut slot = pooled.wrapped.take().expect("object was just checked out");
+        let (item, output) = f(slot.item).await;
+        slot.item = item;
+        pooled.wrapped = Some(slot);
+        pooled.release().await?;
+        Ok(output)
+    }
+
+    /**
+    Experimental alternative to [`Pool::get`] which limits checkouts with a
+    semaphore instead of channels. It first waits for one of `size` permits,
+    then takes an idle object or creates one if there is none. Waiting for
+    a permit is cancellation safe, and a checkout cancelled while creating
+    gives its permit back.
+
+    Objects checked out this way are kept apart from the rest of the pool:
+    they have their own `size` permits and idle objects, are not counted in
+    [`Pool::stats`], and go back to this path when released or dropped.
+    The number of permits is the pool's size when it was built, and isn't
+    changed by [`Pool::resize`].
+    */
+    pub async fn get_via_semaphore(&self) -> Result<SemaphorePooled<T>> {
+        if self.usage.is_closed() {
+            return Err(LazyPoolError::Closed);
+        }
+        let permit = self
+            .semaphore_slots
+            .permits
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|_| LazyPoolError::Closed)?;
+        let idle = self.semaphore_slots.idle.lock().unwrap().pop_front();
+        let slot = match idle {
+            Some(slot) => slot,
+            None => self.create().await?,
+        };
+        log!(
+            self.log_levels.get,
+            "checked out item through semaphore (object_id = {})",
+            slot.id
+        );
+        Ok(SemaphorePooled::new(
+            slot,
+            self.semaphore_slots.clone(),
+            permit,
+        ))
+    }
+
+    /**
+    Same as [`Pool::get`], except that when no object is available right
+    away it creates a temporary overflow object instead of waiting, as long
+    as fewer than `max_overflow` of them exist. Overflow objects don't count
+    against the pool's capacity and are dropped when released. Once the
+    overflow limit is reached it waits like [`Pool::get`].
+
+    ```
+    # use futures::executor::block_on;
+    # use lazy_pool::Pool;
+
+    # struct AnyObject;
+
+    block_on(async {
+        let pool = Pool::new(1, Box::new(|| AnyObject)).await.unwrap();
+        let object = pool.get().await;
+        let overflow = pool.get_or_create_overflow_with_limit(1).await;
+        overflow.release().await.unwrap();
+        object.release().await.unwrap();
+    });
+    ```
+    */
+    pub async fn get_or_create_overflow_with_limit(&self, max_overflow: usize) -> Pooled<T> {
+        let started = Instant::now();
+        self.wait_if_closed().await;
+        self.throttle().await;
+        if let Some(slot) = self.try_next_available() {
+            return self
+                .after_checkout(self.checkout(slot, false, started))
+                .await;
+        }
+        let claimed = self
+            .overflow_count
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |count| {
+                (count < max_overflow).then_some(count + 1)
+            })
+            .is_ok();
+        if claimed {
+            log!(self.log_levels.create, "creating overflow object");
+            match self.create().await {
+                Ok(slot) => {
+                    return self
+                        .after_checkout(self.checkout(slot, true, started))
+                        .await
+                }
+                Err(err) => {
+                    warn!("failed to create overflow object: {err:?}");
+                    self.report_error(err);
+                    self.overflow_count.fetch_sub(1, Ordering::SeqCst);
+                }
+            }
+        }
+        self.checkout_next(started).await
+    }
+
+    fn checkout(&self, slot: Slot<T>, overflow: bool, started: Instant) -> Pooled<T> {
+        let checkout_id = self.record_checkout(&slot, overflow, started);
+        Pooled {
+            wrapped: Some(slot),
+            pool_name: self.name.clone(),
+            tainted: false,
+            checkout_id,
+            release_level: self.log_levels.release,
+            usage: self.usage.clone(),
+            return_validator: self.return_validator.clone(),
+            post_return_hook: self.post_return_hook.clone(),
+            shrink_to: self.shrink_to,
+            callbacks: self.callbacks.clone(),
+            overflow_count: overflow.then(|| self.overflow_count.clone()),
+            waiters: self.waiters.clone(),
+            create_sender: self.create_sender.clone(),
+            return_sender: self.return_sender.clone(),
+            error_handler: self.error_handler.clone(),
+            checked_out: self.checked_out.clone(),
+            holder: None,
+            observer: None,
+            on_return: None,
+            telemetry: None,
+            #[cfg(feature = "opentelemetry")]
+            span: None,
+        }
+    }
+
+    /// Accounts for `slot` being handed out, returning the checkout's id.
+    fn record_checkout(&self, slot: &Slot<T>, overflow: bool, started: Instant) -> u64 {
+        let wait = started.elapsed();
+        self.activity.record(Activity::Checkout { wait });
+        #[cfg(feature = "opentelemetry")]
+        self.telemetry.checked_out(wait);
+        if !overflow {
+            self.usage.checked_out();
+        }
+        self.callbacks.notify(PoolEvent::Checkout, slot.id);
+        self.checked_out
+            .lock()
+            .unwrap()
+            .insert(slot.id, Instant::now());
+        let checkout_id = self.checkout_counter.fetch_add(1, Ordering::Relaxed);
+        log!(
+            self.log_levels.get,
+            "checked out item (checkout_id = {checkout_id}, object_id = {})",
+            slot.id
+        );
+        checkout_id
+    }
+
+    /**
+    Same as [`Pool::get`], but the checked out object borrows the pool
+    instead of holding handles to it, which saves cloning them on every
+    checkout, e.g. in tight loops. The object has to be released before the
+    pool goes out of scope.
+
+    ```
+    # use futures::executor::block_on;
+    # use lazy_pool::Pool;
+
+    block_on(async {
+        let pool = Pool::new(1, Box::new(|| 0)).await.unwrap();
+        for _ in 0..3 {
+            *pool.get_pooled_mut().await += 1;
+        }
+        assert_eq!(3, *pool.get_pooled_mut().await);
+    });
+    ```
+    */
+    pub async fn get_pooled_mut(&self) -> PooledMut<'_, T> {
+        let started = Instant::now();
+        log!(self.log_levels.get, "getting item");
+        self.wait_if_closed().await;
+        self.throttle().await;
+        let slot = self.next_available().await;
+        let checkout_id = self.record_checkout(&slot, false, started);
+        let pooled = PooledMut::new(self, slot, checkout_id);
+        self.replenish().await;
+        if let Some(hook) = &self.post_checkout_hook {
+            hook(&pooled).await;
+        }
+        pooled
+    }
+
+    /**
+    Releases an object checked out through [`Pool::get_pooled_mut`], the
+    same way [`Pooled::release`] releases objects.
+    */
+    fn return_borrowed(&self, slot: Slot<T>, tainted: bool, checkout_id: u64) -> Result<()> {
+        self.releaser()
+            .release(slot, tainted, checkout_id, ReleaseHooks::default())
+    }
+
+    fn releaser(&self) -> Releaser<'_, T> {
+        Releaser {
+            pool_name: &self.name,
+            release_level: self.log_levels.release,
+            usage: &self.usage,
+            return_validator: self.return_validator.as_ref(),
+            post_return_hook: self.post_return_hook.as_ref(),
+            shrink_to: self.shrink_to,
+            callbacks: &self.callbacks,
+            waiters: &self.waiters,
+            return_sender: &self.return_sender,
+            create_sender: &self.create_sender,
+            error_handler: self.error_handler.as_ref(),
+            checked_out: &self.checked_out,
+        }
+    }
+
+    /**
+    Same as [`Pool::get`], with the checked out object pinned on the heap,
+    e.g. to poll a pooled future. Dropping the pinned handle releases the
+    object like dropping [`Pooled`] does. Objects move back into the pool
+    on release, so types which must not move once pinned, such as `async`
+    blocks, are pooled boxed and pinned, as `Pin<Box<T>>`.
+
+    ```
+    # use futures::executor::block_on;
+    # use lazy_pool::Pool;
+    # use std::{future::Future, pin::Pin};
+
+    type Job = Pin<Box<dyn Future<Output = usize> + Send>>;
+
+    block_on(async {
+        let pool = Pool::new(1, Box::new(|| -> Job { Box::pin(async { 42 }) }))
+            .await
+            .unwrap();
+        let mut job = pool.get_pin().await;
+        assert_eq!(42, job.as_mut().await);
+    });
+    ```
+    */
+    pub async fn get_pin(&self) -> Pin<Box<Pooled<T>>> {
+        Box::pin(self.get().await)
+    }
+
+    /**
+    Same as [`Pool::get`], which already gives exclusive access: a given
+    object is only ever held by one [`Pooled`] at a time, and nobody else
+    can use it until it is released. Use this when the object is mutated
+    or holds per-use state, such as a connection in the middle of a
+    transaction. To let several readers use one object at the same time,
+    use [`Pool::get_shared`] instead.
+
+    ```
+    # use futures::executor::block_on;
+    # use lazy_pool::Pool;
+
+    block_on(async {
+        let pool = Pool::new(1, Box::new(|| Vec::<u32>::new())).await.unwrap();
+        let mut buffer = pool.get_exclusive().await;
+        buffer.push(1);
+        // Nobody else can get hold of the buffer until it is released.
+        assert!(pool.try_get().is_none());
+        buffer.release().await.unwrap();
+    });
+    ```
+    */
+    pub async fn get_exclusive(&self) -> Pooled<T> {
+        self.get().await
+    }
+
+    /**
+    Same as [`Pool::get`], reporting the checkout and the release of the
+    object to `observer` without registering anything on the pool, unlike
+    [`Pool::monitor`].
+    */
+    pub async fn get_with_observer<O>(&self, observer: O) -> Pooled<T>
+    where
+        O: PoolObserver<T> + 'static,
+    {
+        let mut pooled = self.get().await;
+        let slot = pooled
+            .wrapped
+            .as_ref()
+            .expect("object was just checked out");
+        let from = if slot.fresh {
+            CheckoutSource::Created
+        } else {
+            CheckoutSource::Idle
+        };
+        observer.on_checkout(&slot.item, from);
+        pooled.observer = Some(Box::new(observer));
+        pooled
+    }
+
+    /**
+    Same as [`Pool::get`], leasing the object for `duration`, which starts
+    once the object is checked out. An object held for longer than that is
+    replaced instead of being reused, e.g. because a connection held that
+    long may have been left in an unknown state.
+    */
+    pub async fn get_with_lease(&self, duration: Duration) -> LeasedPooled<T> {
+        PooledWith::new(self.get().await, Lease::new(duration))
+    }
+
+    /**
+    Same as [`Pool::get`], reporting the checkout and the usage of the
+    object to `tracker`. Pass a mutable reference to accumulate usage
+    across checkouts.
+
+    ```
+    # use futures::executor::block_on;
+    # use lazy_pool::{Pool, UsageTracker};
+
+    # struct Connection;
+
+    #[derive(Default)]
+    struct QueryCount(u64);
+
+    impl UsageTracker<Connection> for QueryCount {
+        fn record_checkout(&mut self, _: &Connection) {}
+
+        fn record_return(&mut self, _: &Connection, queries: u64) {
+            self.0 += queries;
+        }
+    }
+
+    block_on(async {
+        let pool = Pool::new(1, Box::new(|| Connection)).await.unwrap();
+        let mut queries = QueryCount::default();
+        let mut connection = pool.get_with_usage_tracker(&mut queries).await;
+        connection.report_usage(3);
+        connection.release().await.unwrap();
+        assert_eq!(3, queries.0);
+    });
+    ```
+    */
+    pub async fn get_with_usage_tracker<U>(&self, tracker: U) -> TrackedPooled<T, U>
+    where
+        U: UsageTracker<T>,
+    {
+        PooledWith::new(self.get().await, Tracked::new(tracker))
+    }
+
+    /**
+    Same as [`Pool::get`], recording when the checkout started, when the
+    pool handed out the object and when the checkout completed, to break
+    down where checkouts spend their time without a tracing or metrics
+    setup.
+
+    ```
+    # use futures::executor::block_on;
+    # use lazy_pool::Pool;
+
+    # struct AnyObject;
+
+    block_on(async {
+        let pool = Pool::new(1, Box::new(|| AnyObject)).await.unwrap();
+        let object = pool.get_with_profiling().await;
+        let (result, profile) = object.release().await;
+        result.unwrap();
+        assert!(profile.queue_wait <= profile.total_overhead);
+    });
+    ```
+    */
+    pub async fn get_with_profiling(&self) -> ProfiledPooled<T> {
+        let queued_at = Instant::now();
+        log!(self.log_levels.get, "getting item");
+        self.wait_if_closed().await;
+        self.throttle().await;
+        let slot = self.next_available().await;
+        let dequeued_at = Instant::now();
+        let pooled = self.checkout(slot, false, queued_at);
+        self.replenish().await;
+        let pooled = self.after_checkout(pooled).await;
+        PooledWith::new(pooled, Profile::new(queued_at, dequeued_at, Instant::now()))
+    }
+
+    /**
+    Same as [`Pool::get`], running `middleware` around the checkout and
+    the release of the object. Fails with the middleware's error, without
+    checking out an object, if [`PoolMiddleware::pre_checkout`] fails.
+    Layers are combined as tuples.
+
+    ```
+    # use futures::executor::block_on;
+    # use lazy_pool::{LazyPoolError, Pool, PoolMiddleware};
+
+    # struct AnyObject;
+
+    struct Logging;
+
+    impl PoolMiddleware<AnyObject> for Logging {
+        fn post_return(&self, _: &AnyObject, tainted: bool) {
+            println!("object released (tainted = {tainted})");
+        }
+    }
+
+    block_on(async {
+        let pool = Pool::new(1, Box::new(|| AnyObject)).await.unwrap();
+        let object = pool.get_with_middleware((Logging, Logging)).await.unwrap();
+        object.release().await.unwrap();
+    });
+    ```
+    */
+    pub async fn get_with_middleware<M>(&self, middleware: M) -> Result<Pooled<T>>
+    where
+        M: PoolMiddleware<T>,
+    {
+        middleware.pre_checkout(self).await?;
+        let mut pooled = self.get().await;
+        pooled.observer = Some(Box::new(MiddlewareObserver(middleware)));
+        Ok(pooled)
+    }
+
+    /**
+    Same as [`Pool::get`], pairing the object with `dep`, a resource which
+    is only needed while the object is in use. The object is released as
+    usual and `dep` is dropped along with it, so the two can't outlive each
+    other by mistake.
+
+    ```
+    # use futures::executor::block_on;
+    # use lazy_pool::Pool;
+
+    # struct Connection;
+
+    struct Transaction {
+        id: u64,
+    }
+
+    block_on(async {
+        let pool = Pool::new(1, Box::new(|| Connection)).await.unwrap();
+        let connection = pool.get_with_dependency(Transaction { id: 1 }).await;
+        assert_eq!(1, connection.dep().id);
+        connection.release().await.unwrap();
+    });
+    ```
+    */
+    pub async fn get_with_dependency<D>(&self, dep: D) -> PooledWithDep<T, D> {
+        PooledWith::new(self.get().await, Dep(dep))
+    }
+
+    /**
+    Same as [`Pool::get`], for the caller identified by `caller_id`, failing
+    with [`LazyPoolError::QuotaExceeded`] without waiting if `quota` doesn't
+    allow it another object. The checkout is reserved in `quota` through
+    [`QuotaTracker::try_reserve`] before waiting for an object, and given
+    back once the object is released or if the checkout is cancelled.
+    */
+    pub async fn get_with_quota<Q>(&self, caller_id: &str, quota: Q) -> Result<Pooled<T>>
+    where
+        Q: QuotaTracker,
+    {
+        if !quota.try_reserve(caller_id) {
+            return Err(LazyPoolError::QuotaExceeded);
+        }
+        let reservation = QuotaReservation::new(caller_id, quota);
+        let mut pooled = self.get().await;
+        pooled.observer = Some(Box::new(reservation.into_observer()));
+        Ok(pooled)
+    }
+
+    /**
+    Same as [`Pool::get`], tracing the checkout as a `{prefix}.pool.checkout`
+    span which is a child of `parent`, e.g. the context of the request the
+    object is needed for. The time spent waiting for the object is recorded
+    as the span's `pool.wait_duration` attribute, in seconds. The span is
+    ended once the object is released.
+    */
+    #[cfg(feature = "opentelemetry")]
+    pub async fn get_with_span_context(&self, parent: opentelemetry::Context) -> Pooled<T> {
+        let mut span = self.telemetry.child_span("checkout", &parent);
+        let started = Instant::now();
+        let mut pooled = self.get().await;
+        telemetry::record_wait(&mut span, started.elapsed());
+        pooled.span = Some(span);
+        pooled
+    }
+
+    /**
+    Same as [`Pool::get`], recording the checkout's spans and metrics
+    through `context`, for whichever telemetry framework it implements
+    [`TelemetryContext`] for. See [`TelemetryContext`] for what is recorded.
+    */
+    pub async fn get_with_telemetry_context<C>(&self, context: C) -> Pooled<T>
+    where
+        C: TelemetryContext,
+    {
+        let mut telemetry = CheckoutTelemetry::start(Box::new(context));
+        let started = Instant::now();
+        let mut pooled = self.get().await;
+        telemetry.checked_out(started.elapsed());
+        pooled.telemetry = Some(telemetry);
+        pooled
+    }
+
+    /**
+    Same as [`Pool::get`], with the object wrapped in a [`ReturnGuard`].
+    Dropping the guard hands the object to the accompanying
+    [`ReleaseTask`], which releases it and resolves to the outcome, so
+    that release errors can be handled even when the guard is dropped in
+    synchronous code.
+
+    ```
+    # use lazy_pool::Pool;
+
+    # struct AnyObject;
+
+    # #[tokio::main]
+    # async fn main() {
+    let pool = Pool::new(1, Box::new(|| AnyObject)).await.unwrap();
+    let (object, release) = pool.get_returning_guard().await;
+    let release = tokio::spawn(release);
+    drop(object);
+    release.await.unwrap().unwrap();
+    # }
+    ```
+    */
+    pub async fn get_returning_guard(&self) -> (ReturnGuard<T>, ReleaseTask<T>) {
+        ReturnGuard::new(self.get().await)
+    }
+
+    /**
+    Same as [`Pool::get`], calling `on_return` with the object once it is
+    released, whether it goes back into the pool or is dropped because it
+    is tainted. Lets each caller attach its own cleanup to a checkout.
+    */
+    pub async fn get_async_callback<F>(&self, on_return: F) -> Pooled<T>
+    where
+        F: FnOnce(&T) + Send + 'static,
+    {
+        let mut pooled = self.get().await;
+        pooled.on_return = Some(Box::new(on_return));
+        pooled
+    }
+
+    /**
+    Same as [`Pool::get`], but creates objects concurrently through the
+    factory set with [`PoolBuilder::background_factory`]. Requires the
+    `background-factory` feature.
+
+    The object is created in a task spawned on the current Tokio runtime
+    and handed over once it is ready, so that several objects can be
+    created at the same time. Unlike [`Pool::get`], this lets go of the
+    pool while the object is created, so that other checkouts can pick up
+    objects released in the meantime. Pools built without a background
+    factory still borrow their factory mutably while it produces, so their
+    objects are created one at a time.
+    */
+    #[cfg(feature = "background-factory")]
+    pub async fn get_nonblocking_factory(&self) -> Pooled<T> {
+        let started = Instant::now();
+        log!(self.log_levels.get, "getting item");
+        self.wait_if_closed().await;
+        self.throttle().await;
+        let slot = self.next_available_via(true).await;
+        let pooled = self.checkout(slot, false, started);
+        self.replenish().await;
+        self.after_checkout(pooled).await
+    }
+
+    /**
+    Same as [`Pool::get`], with the object viewed as a `U` it can be
+    borrowed as. For pools of boxed trait objects this gives access to the
+    trait object itself.
+
+    ```
+    # use futures::executor::block_on;
+    # use lazy_pool::Pool;
+
+    trait Greeter: Send {
+        fn greet(&self) -> String;
+    }
+
+    struct English;
+
+    impl Greeter for English {
+        fn greet(&self) -> String {
+            String::from("hello")
+        }
+    }
+
+    block_on(async {
+        let pool = Pool::new(1, Box::new(|| Box::new(English) as Box<dyn Greeter>))
+            .await
+            .unwrap();
+        let greeter = pool.get_as::<dyn Greeter>().await;
+        assert_eq!("hello", greeter.greet());
+        greeter.release().await.unwrap();
+    });
+    ```
+    */
+    pub async fn get_as<U>(&self) -> PooledAs<T, U>
+    where
+        T: Borrow<U>,
+        U: ?Sized,
+    {
+        PooledAs::new(self.get().await)
+    }
+
+    /**
+    Returns a view of this pool which hands out its objects wrapped in
+    `W`, unwrapping them again when they are released. Lets wrappers add
+    cleanup to checkouts without a pool of their own.
+
+    ```
+    # use futures::executor::block_on;
+    # use lazy_pool::{Pool, Wrap};
+
+    struct Connection {
+        in_transaction: bool,
+    }
+
+    struct RollbackOnReturn(Connection);
+
+    impl Wrap<Connection> for RollbackOnReturn {
+        fn wrap(connection: Connection) -> Self {
+            Self(connection)
+        }
+
+        fn unwrap(mut self) -> Connection {
+            self.0.in_transaction = false;
+            self.0
+        }
+    }
+
+    block_on(async {
+        let pool = Pool::new(1, Box::new(|| Connection { in_transaction: false }))
+            .await
+            .unwrap();
+        let connections = pool.with_object_wrapper::<RollbackOnReturn>();
+        let mut connection = connections.get().await;
+        connection.0.in_transaction = true;
+        connection.release().await.unwrap();
+        assert!(!pool.get().await.in_transaction);
+    });
+    ```
+    */
+    pub fn with_object_wrapper<W>(&self) -> WrappedPool<'_, T, W>
+    where
+        W: Wrap<T>,
+    {
+        WrappedPool::new(self)
+    }
+
+    /**
+    Same as [`Pool::get_exclusive`], but fails with
+    [`LazyPoolError::Reentrant`] right away if the current task already
+    holds an object checked out through this method, instead of possibly
+    waiting forever for the task to release it, e.g. when a helper called
+    while holding an object uses the same pool. Tasks are told apart by
+    their wakers, so futures polled by combinators which wrap the waker,
+    such as `FuturesUnordered`, count as separate tasks.
+    */
+    pub async fn get_exclusive_with_reentrancy_guard(&self) -> Result<Pooled<T>> {
+        let waker = poll_fn(|cx| Poll::Ready(cx.waker().clone())).await;
+        if self.holders.is_held_by(&waker) {
+            return Err(LazyPoolError::Reentrant);
+        }
+        let mut pooled = self.get().await;
+        pooled.holder = Some(self.holders.hold(pooled.checkout_id, waker));
+        Ok(pooled)
+    }
+
+    /**
+    Checks out an object which can be handed to several readers at once.
+    Cloning the returned [`SharedPooled`] shares the same checkout, and the
+    object goes back to the pool once every clone has been dropped.
+
+    ```
+    # use futures::executor::block_on;
+    # use lazy_pool::Pool;
+
+    block_on(async {
+        let pool = Pool::new(1, Box::new(|| String::from("config"))).await.unwrap();
+        let reader = pool.get_shared().await;
+        let other_reader = reader.clone();
+        assert_eq!(*reader, *other_reader);
+        drop(reader);
+        drop(other_reader);
+        assert_eq!(1, pool.stats().idle);
+    });
+    ```
+    */
+    pub async fn get_shared(&self) -> SharedPooled<T> {
+        SharedPooled::new(self.get().await)
+    }
+
+    /**
+    Same as [`Pool::get`], returned as a named future type which can be
+    stored, e.g. when building a pipeline of futures ahead of running it.
+    Nothing is checked out until the future is first polled.
+
+    ```
+    # use futures::executor::block_on;
+    # use lazy_pool::Pool;
+
+    # struct AnyObject;
+
+    block_on(async {
+        let pool = Pool::new(1, Box::new(|| AnyObject)).await.unwrap();
+        let pending = pool.get_lazily();
+        assert_eq!(0, pool.stats().in_use);
+        let object = pending.await;
+        assert_eq!(1, pool.stats().in_use);
+        object.release().await.unwrap();
+    });
+    ```
+    */
+    pub fn get_lazily(&self) -> LazyGet<'_, T> {
+        LazyGet::new(self)
+    }
+
+    /**
+    Same as [`Pool::get_timeout`] with the maximum wait set through
+    [`PoolBuilder::with_max_wait`] or [`PoolConfig::max_wait`]. Without one,
+    it waits as long as [`Pool::get`] does, but fails with
+    [`LazyPoolError::Closed`] if the pool has been shut down.
+    */
+    pub async fn get_with_max_wait(&self) -> Result<Pooled<T>> {
+        match self.max_wait {
+            Some(max_wait) => self.get_timeout(max_wait).await,
+            None if self.usage.is_closed() => Err(LazyPoolError::Closed),
+            None => Ok(self.get().await),
+        }
+    }
+
+    /**
+    Same as [`Pool::get`] but fails with [`LazyPoolError::Timeout`] if no
+    object becomes available within `timeout`.
+    */
+    pub async fn get_timeout(&self, timeout: Duration) -> Result<Pooled<T>> {
+        if self.usage.is_closed() {
+            return Err(LazyPoolError::Closed);
+        }
+        select_biased! {
+            pooled = self.get().fuse() => Ok(pooled),
+            _ = Delay::new(timeout).fuse() => {
+                log!(self.log_levels.get, "timed out waiting for item");
+                Err(LazyPoolError::Timeout)
+            }
+        }
+    }
+
+    /**
+    Same as [`Pool::get`], but runs the checkout in the caller's current
+    tracing span, captured when this is called, so that events of the
+    factory creating an object for the checkout are recorded as children of
+    that span even if the checkout is awaited elsewhere. Requires the
+    `tracing` feature.
+    */
+    #[cfg(feature = "tracing")]
+    pub fn get_with_tracing_propagation(&self) -> impl Future<Output = Pooled<T>> + '_ {
+        use tracing::Instrument;
+        self.get().instrument(tracing::Span::current())
+    }
+
+    /**
+    Same as [`Pool::get`], but gives up with [`LazyPoolError::Cancelled`]
+    once the [`CancellationToken`] of the current Tokio task is cancelled,
+    e.g. when the task serving a request is told to stop. Waits like
+    [`Pool::get`] outside of a [`CancellationToken::scope`]. Locks on the
+    pool are released as soon as the checkout is given up, so other
+    checkouts can go ahead. Requires the `cancellation` feature.
+
+    ```
+    # use lazy_pool::{CancellationToken, LazyPoolError, Pool};
+
+    # struct AnyObject;
+
+    # #[tokio::main]
+    # async fn main() {
+    let pool = Pool::new(1, Box::new(|| AnyObject)).await.unwrap().into_shared();
+    let object = pool.get().await;
+    let token = CancellationToken::new();
+    let task_pool = pool.clone();
+    let task = tokio::spawn(token.scope(async move {
+        task_pool.get_scoped_to_tokio_context().await.map(|_| ())
+    }));
+    token.cancel();
+    assert!(matches!(task.await.unwrap(), Err(LazyPoolError::Cancelled)));
+    object.release().await.unwrap();
+    # }
+    ```
+    */
+    #[cfg(feature = "cancellation")]
+    pub async fn get_scoped_to_tokio_context(&self) -> Result<Pooled<T>> {
+        let Some(token) = CancellationToken::current() else {
+            return Ok(self.get().await);
+        };
+        select_biased! {
+            _ = token.cancelled().fuse() => {
+                log!(self.log_levels.get, "checkout cancelled");
+                Err(LazyPoolError::Cancelled)
+            }
+            pooled = self.get().fuse() => Ok(pooled),
+        }
+    }
+
+    /**
+    Same as [`Pool::get`], but first waits for the delay set through
+    [`PoolBuilder::with_backpressure_delay`] if `signal` is raised, e.g. by a
+    load shedding component telling the system to slow down. Checkouts go
+    ahead right away while the signal is lowered.
+
+    ```
+    # use futures::executor::block_on;
+    # use lazy_pool::{Pool, SyncFactory};
+    # use std::{sync::{atomic::AtomicBool, Arc}, time::Duration};
+
+    # struct AnyObject;
+
+    block_on(async {
+        let pool = Pool::builder()
+            .factory(SyncFactory::from(|| AnyObject))
+            .with_backpressure_delay(Duration::from_millis(10))
+            .build()
+            .await
+            .unwrap();
+        let under_load = Arc::new(AtomicBool::new(true));
+        let object = pool.get_with_backpressure_signal(under_load.clone()).await;
+        object.release().await.unwrap();
+    });
+    ```
+    */
+    pub async fn get_with_backpressure_signal(&self, signal: Arc<AtomicBool>) -> Pooled<T> {
+        if signal.load(Ordering::Relaxed) {
+            log!(
+                self.log_levels.get,
+                "backing off for {:?} before checkout",
+                self.backpressure_delay
+            );
+            Delay::new(self.backpressure_delay).await;
+        }
+        self.get().await
+    }
+
+    /**
+    Same as [`Pool::get_timeout`], spending a unit of `budget` whenever the
+    checkout fails. Once `budget` is exhausted, fails right away with
+    [`LazyPoolError::ErrorBudgetExhausted`] without touching the pool, until
+    the budget is reset.
+
+    ```
+    # use futures::executor::block_on;
+    # use lazy_pool::{ErrorBudget, Pool};
+    # use std::time::Duration;
+
+    # struct AnyObject;
+
+    block_on(async {
+        let pool = Pool::new(1, Box::new(|| AnyObject)).await.unwrap();
+        let budget = ErrorBudget::new(100);
+        let object = pool
+            .get_with_error_budget(&budget, Duration::from_secs(1))
+            .await
+            .unwrap();
+        object.release().await.unwrap();
+    });
+    ```
+    */
+    pub async fn get_with_error_budget(
+        &self,
+        budget: &ErrorBudget,
+        timeout: Duration,
+    ) -> Result<Pooled<T>> {
+        if budget.is_exhausted() {
+            log!(self.log_levels.get, "error budget exhausted");
+            return Err(LazyPoolError::ErrorBudgetExhausted);
+        }
+        let result = self.get_timeout(timeout).await;
+        if result.is_err() {
+            budget.spend();
+        }
+        result
+    }
+
+    /**
+    Checks out an object like [`Pool::get_timeout`] and returns what `f`
+    makes of it. If no object becomes available within `timeout`, returns
+    `fallback` instead, e.g. a cached or degraded response.
+
+    ```
+    # use futures::executor::block_on;
+    # use lazy_pool::Pool;
+    # use std::time::Duration;
+
+    block_on(async {
+        let pool = Pool::new(1, Box::new(|| String::from("live"))).await.unwrap();
+        let object = pool.get().await;
+        let value = pool
+            .get_with_fallback(Duration::from_millis(10), |value| value.clone(), "cached".into())
+            .await;
+        assert_eq!("cached", value);
+        object.release().await.unwrap();
+    });
+    ```
+    */
+    pub async fn get_with_fallback<F, R>(&self, timeout: Duration, f: F, fallback: R) -> R
+    where
+        F: FnOnce(&mut T) -> R,
+    {
+        let Ok(mut pooled) = self.get_timeout(timeout).await else {
+            log!(self.log_levels.get, "using fallback value");
+            return fallback;
+        };
+        let result = f(&mut pooled);
+        if let Err(err) = pooled.release().await {
+            log::error!("failed to release object: {err:?}");
+        }
+        result
+    }
+
+    /**
+    Same as [`Pool::get`] but fails with [`LazyPoolError::Timeout`] if no
+    object becomes available before `deadline`.
+    */
+    pub async fn get_with_deadline(&self, deadline: Instant) -> Result<Pooled<T>> {
+        self.get_timeout(deadline.saturating_duration_since(Instant::now()))
+            .await
+    }
+
+    /**
+    Calls [`Pool::get_with_deadline`] if there is a deadline, or
+    [`Pool::get`] otherwise. Matches how `tower` middleware hands out
+    deadlines, which it keeps as an optional [`Instant`] in the request's
+    extensions.
+
+    ```
+    # use futures::executor::block_on;
+    # use lazy_pool::Pool;
+    # use std::time::{Duration, Instant};
+
+    # struct AnyObject;
+
+    #[derive(Clone, Copy)]
+    struct Deadline(Instant);
+
+    block_on(async {
+        let pool = Pool::new(1, Box::new(|| AnyObject)).await.unwrap();
+        let mut request = http::Request::new(());
+        request
+            .extensions_mut()
+            .insert(Deadline(Instant::now() + Duration::from_secs(1)));
+
+        // Inside `tower::Service::call`:
+        let deadline = request.extensions().get::<Deadline>().map(|d| d.0);
+        let object = pool.get_deadline_aware(deadline).await.unwrap();
+        object.release().await.unwrap();
+    });
+    ```
+    */
+    pub async fn get_deadline_aware(&self, deadline: Option<Instant>) -> Result<Pooled<T>> {
+        match deadline {
+            Some(deadline) => self.get_with_deadline(deadline).await,
+            None => Ok(self.get().await),
+        }
+    }
+
+    async fn next_available(&self) -> Slot<T> {
+        self.next_available_via(false).await
+    }
+
+    /**
+    Waits for an idle object, or for a free slot to create one in. With
+    `nonblocking`, the receivers are let go of while the object is created
+    through [`Pool::create_nonblocking`], and taken again if it fails.
+    */
+    async fn next_available_via(&self, nonblocking: bool) -> Slot<T> {
+        let mut receivers = None;
+        loop {
+            let (return_receiver, create_receiver) = match &mut receivers {
+                Some(receivers) => receivers,
+                None => receivers.insert((
+                    self.return_receiver.lock().await,
+                    self.create_receiver.lock().await,
+                )),
+            };
+            let idle = select_biased! {
+                item = return_receiver.next() => Some(item),
+                _ = create_receiver.next() => None,
+            };
+            match idle {
+                Some(Some(item)) => {
+                    log!(self.log_levels.get, "using returned object");
+                    self.usage.reused();
+                    return item;
+                }
+                Some(None) => {
+                    // Shut down: let go of the receivers so that
+                    // `drain_and_shutdown` can take the idle objects.
+                    drop(receivers);
+                    return self.wait_forever().await;
+                }
+                None => {}
+            }
+            if nonblocking {
+                receivers = None;
+            }
+            log!(self.log_levels.create, "creating object");
+            // Hand the slot back if creation fails or this future is
+            // dropped mid-creation, e.g. because a timeout elapsed.
+            let mut guard = CreateGuard(Some(self.create_sender.clone()));
+            let created = if nonblocking {
+                self.create_nonblocking().await
+            } else {
+                self.create().await
+            };
+            match created {
+                Ok(slot) => {
+                    guard.0 = None;
+                    return slot;
+                }
+                Err(err) => {
+                    warn!("failed to create object: {err:?}");
+                    self.report_error(err);
+                }
+            }
+        }
+    }
+
+    async fn take_latest_idle(&self) -> Option<Slot<T>> {
+        let mut return_receiver = self.return_receiver.lock().await;
+        let mut idle = drain_idle(&mut return_receiver);
+        let latest = idle.pop()?;
+        self.usage.reused();
+        let mut return_sender = self.return_sender.clone();
+        for slot in idle {
+            if let Err(err) = return_sender.send(slot).await {
+                warn!("failed to put back idle object: {err:?}");
+            }
+        }
+        Some(latest)
+    }
+
+    /// Never resolves once the pool has been shut down, see [`Pool::drain_and_shutdown`].
+    async fn wait_if_closed(&self) {
+        if self.usage.is_closed() {
+            self.wait_forever().await
+        }
+    }
+
+    async fn wait_forever<R>(&self) -> R {
+        log!(
+            self.log_levels.get,
+            "pool has been shut down, checkout will never complete"
+        );
+        pending().await
+    }
+
+    fn try_take_idle(&self) -> Option<Slot<T>> {
+        let mut return_receiver = self.return_receiver.try_lock()?;
+        let slot = return_receiver.try_recv().ok()?;
+        log!(self.log_levels.get, "using returned object");
+        self.usage.reused();
+        Some(slot)
+    }
+
+    fn try_next_available(&self) -> Option<Slot<T>> {
+        if let Some(slot) = self.try_take_idle() {
+            return Some(slot);
+        }
+        let mut create_receiver = self.create_receiver.try_lock()?;
+        create_receiver.try_recv().ok()?;
+        log!(self.log_levels.create, "creating object");
+        let mut guard = CreateGuard(Some(self.create_sender.clone()));
+        match self.create().now_or_never()? {
+            Ok(slot) => {
+                guard.0 = None;
+                Some(slot)
+            }
+            Err(err) => {
+                warn!("failed to create object: {err:?}");
+                self.report_error(err);
+                None
+            }
+        }
+    }
+
+    async fn create(&self) -> Result<Slot<T>> {
+        #[cfg(feature = "opentelemetry")]
+        let _span = self.telemetry.span("create");
+        if let Some(hook) = &self.pre_create_hook {
+            hook().await;
+        }
+        let result = self.factory.lock().await.try_produce().await;
+        self.created(result)
+    }
+
+    /**
+    Same as [`Pool::create`], but runs a background factory in a spawned
+    task, without locking the pool's factory, so that other creations can
+    run at the same time.
+    */
+    async fn create_nonblocking(&self) -> Result<Slot<T>> {
+        #[cfg(feature = "background-factory")]
+        if let Some(factory) = &self.background_factory {
+            #[cfg(feature = "opentelemetry")]
+            let _span = self.telemetry.span("create");
+            if let Some(hook) = &self.pre_create_hook {
+                hook().await;
+            }
+            let (sender, receiver) = oneshot::channel();
+            let produce = factory();
+            tokio::spawn(async move {
+                // The checkout may have been given up in the meantime.
+                let _ = sender.send(produce.await);
+            });
+            let result = receiver
+                .await
+                .map_err(|_| FactoryError::from("factory task panicked"));
+            return self.created(result);
+        }
+        self.create().await
+    }
+
+    /// Accounts for the outcome of a factory call.
+    fn created(&self, result: StdResult<T, FactoryError>) -> Result<Slot<T>> {
+        let item = match result {
+            Ok(item) => {
+                self.health.created();
+                self.activity.record(Activity::Create);
+                item
+            }
+            Err(err) => {
+                self.health.failed();
+                self.activity.record(Activity::CreateFailed);
+                return Err(LazyPoolError::Factory(err));
+            }
+        };
+        let slot = Slot::new(item);
+        log!(self.log_levels.create, "created object {}", slot.id);
+        self.callbacks.notify(PoolEvent::Create, slot.id);
+        Ok(slot)
+    }
+
+    /**
+    Creates a pool of `U` which shares the slot budget of this pool. The
+    mapped pool does not have a factory of its own; it produces objects
+    through this pool's factory and maps them with `f`. Objects returned to
+    either pool only become available to that pool, but an object checked
+    out from either of them counts against the shared capacity.
+
+    ```
+    # use futures::executor::block_on;
+    # use lazy_pool::{Pool, get};
+
+    block_on(async {
+        let pool = Pool::new(2, Box::new(|| String::from("hello"))).await.unwrap();
+        let lengths = pool.map(|value| value.len());
+        assert_eq!(5, get!(length = lengths => *length));
+    });
+    ```
+    */
+    pub fn map<U, F>(&self, f: F) -> Pool<U>
+    where
+        U: Send + 'static,
+        F: Fn(T) -> U + Send + Sync + 'static,
+    {
+        self.derive(MapFactory::new(self.factory.clone(), f))
+    }
+
+    /**
+    Like [`Pool::map`], but objects for which `f` returns `None` are
+    discarded and created again, up to the pool's maximum number of create
+    retries (see [`PoolBuilder::with_max_create_retries`]). When all attempts
+    are rejected the creation fails and the checkout starts over, like it
+    does for any failed creation.
+
+    ```
+    # use futures::executor::block_on;
+    # use lazy_pool::{Pool, get};
+
+    block_on(async {
+        let pool = Pool::new(2, Box::new(|| String::from("42"))).await.unwrap();
+        let numbers = pool.filter_map(|value| value.parse::<u32>().ok());
+        assert_eq!(42, get!(number = numbers => *number));
+    });
+    ```
+    */
+    pub fn filter_map<U, F>(&self, f: F) -> Pool<U>
+    where
+        U: Send + 'static,
+        F: Fn(T) -> Option<U> + Send + Sync + 'static,
+    {
+        self.derive(FilterMapFactory::new(
+            self.factory.clone(),
+            f,
+            self.max_create_retries,
+        ))
+    }
+
+    /// Creates a pool sharing this pool's slots but using another factory.
+    fn derive<U, F>(&self, factory: F) -> Pool<U>
+    where
+        U: Send + 'static,
+        F: TryFactory<U> + 'static,
+    {
+        let (return_sender, return_receiver) = mpsc::unbounded();
+        Pool {
+            name: self.name.clone(),
+            factory: Arc::new(Mutex::new(Box::new(factory))),
+            return_receiver: Arc::new(Mutex::new(return_receiver)),
+            create_receiver: self.create_receiver.clone(),
+            return_sender,
+            create_sender: self.create_sender.clone(),
+            checkout_counter: Arc::new(AtomicU64::new(0)),
+            callbacks: Arc::new(Callbacks::default()),
+            log_levels: self.log_levels,
+            usage: self.usage.clone(),
+            return_validator: None,
+            pre_create_hook: self.pre_create_hook.clone(),
+            post_return_hook: None,
+            post_checkout_hook: None,
+            max_create_retries: self.max_create_retries,
+            min_idle: 0,
+            shrink_to: None,
+            overflow_count: Arc::new(AtomicUsize::new(0)),
+            health: Arc::new(Health::new(self.health.failure_threshold())),
+            waiters: Arc::new(Waiters::new()),
+            activity: Arc::new(ActivityLog::new(self.activity.capacity())),
+            rate_limiter: self.rate_limiter.clone(),
+            semaphore_slots: Arc::new(SemaphoreSlots::new(self.stats().capacity)),
+            holders: Arc::new(Holders::default()),
+            max_idle_duration: Arc::new(RwLock::new(*self.max_idle_duration.read().unwrap())),
+            health_check_interval: Arc::new(
+                watch::channel(*self.health_check_interval.subscribe().borrow()).0,
+            ),
+            max_wait: self.max_wait,
+            error_handler: self.error_handler.clone(),
+            checked_out: Arc::default(),
+            backpressure_delay: self.backpressure_delay,
+            lru_eviction: self.lru_eviction,
+            #[cfg(feature = "background-factory")]
+            background_factory: None,
+            #[cfg(feature = "opentelemetry")]
+            telemetry: self.telemetry.clone(),
+        }
+    }
+
+    /**
+    Wraps the pool in an [`Arc`] so it can be shared between tasks and
+    threads.
+
+    ```
+    # use futures::executor::block_on;
+    # use lazy_pool::{Pool, get};
+    # use std::thread;
+
+    # struct AnyObject;
+
+    let pool = block_on(Pool::new(2, Box::new(|| AnyObject))).unwrap().into_shared();
+    let local_pool = pool.clone();
+    thread::spawn(move || block_on(async { get!(object = local_pool => {}) }))
+        .join()
+        .unwrap();
+    ```
+    */
+    pub fn into_shared(self) -> Arc<Self> {
+        Arc::new(self)
+    }
+
+    fn report_error(&self, err: LazyPoolError) {
+        if let Some(handler) = &self.error_handler {
+            handler(err);
+        }
+    }
+
+    /**
+    Creates `n` objects and puts them in the pool, waiting for free slots
+    if needed. `n` is capped to the pool's capacity. Fails if the factory
+    fails, in which case objects created so far remain in the pool.
+    */
+    pub async fn warm(&self, n: usize) -> Result<()> {
+        let n = n.min(self.stats().capacity);
+        log!(self.log_levels.create, "warming {n} objects");
+        for _ in 0..n {
+            let mut create_receiver = self.create_receiver.lock().await;
+            create_receiver.next().await;
+            let mut guard = CreateGuard(Some(self.create_sender.clone()));
+            let slot = self.create().await?;
+            guard.0 = None;
+            drop(create_receiver);
+            self.put_idle(slot).await?;
+        }
+        Ok(())
+    }
+
+    /**
+    Creates up to `n` objects using only the slots which are free right
+    now, without waiting for checked out objects to be returned. Returns
+    the number of objects created, which is 0 on a fully warm pool. Stops
+    early if the factory fails.
+    */
+    pub async fn try_warm(&self, n: usize) -> usize {
+        let Some(mut create_receiver) = self.create_receiver.try_lock() else {
+            return 0;
+        };
+        let mut warmed = 0;
+        while warmed < n && create_receiver.try_recv().is_ok() {
+            let mut guard = CreateGuard(Some(self.create_sender.clone()));
+            match self.create().await {
+                Ok(slot) => {
+                    guard.0 = None;
+                    if let Err(err) = self.put_idle(slot).await {
+                        warn!("failed to warm object: {err:?}");
+                        break;
+                    }
+                    warmed += 1;
+                }
+                Err(err) => {
+                    warn!("failed to warm object: {err:?}");
+                    self.report_error(err);
+                    break;
+                }
+            }
+        }
+        log!(self.log_levels.create, "warmed {warmed} objects");
+        warmed
+    }
+
+    /**
+    Tops up idle objects to `min_idle` using free slots. Skipped if another
+    checkout is waiting on the slots, since that means the pool is busy.
+    */
+    async fn replenish(&self) {
+        if self.stats().idle >= self.min_idle {
+            return;
+        }
+        let Some(mut create_receiver) = self.create_receiver.try_lock() else {
+            return;
+        };
+        while self.stats().idle < self.min_idle && create_receiver.try_recv().is_ok() {
+            log!(self.log_levels.create, "replenishing idle objects");
+            let mut guard = CreateGuard(Some(self.create_sender.clone()));
+            match self.create().await {
+                Ok(slot) => {
+                    guard.0 = None;
+                    if let Err(err) = self.put_idle(slot).await {
+                        warn!("failed to replenish idle objects: {err:?}");
+                    }
+                }
+                Err(err) => {
+                    warn!("failed to replenish idle objects: {err:?}");
+                    self.report_error(err);
+                    break;
+                }
+            }
+        }
+    }
+
+    async fn put_idle(&self, slot: Slot<T>) -> Result<()> {
+        self.usage.returning();
+        let result = self.return_sender.clone().send(slot).await;
+        if result.is_err() {
+            self.usage.reused();
+        }
+        Ok(result?)
+    }
+
+    /**
+    Applies `f` to every idle object in turn, e.g. to broadcast a
+    configuration change. Checked out objects are not affected. Checkouts
+    wait until all idle objects have been processed. If this is cancelled
+    or fails, the objects which haven't been processed are put back as they
+    are.
+
+    A checkout waiting for an object keeps the idle objects locked until it
+    gets one, and this waits for it first. On an exhausted pool with
+    waiting checkouts, it only proceeds once an object is released.
+
+    ```
+    # use futures::executor::block_on;
+    # use lazy_pool::{Pool, get};
+
+    block_on(async {
+        let pool = Pool::new(2, Box::new(|| 0)).await.unwrap();
+        pool.warm(2).await.unwrap();
+        pool.for_each_idle(|value| {
+            *value += 1;
+            async {}
+        })
+        .await
+        .unwrap();
+        assert_eq!(1, get!(value = pool => *value));
+    });
+    ```
+    */
+    pub async fn for_each_idle<F, Fut>(&self, f: F) -> Result<()>
+    where
+        F: Fn(&mut T) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        let mut return_receiver = self.return_receiver.lock().await;
+        let mut idle = TakenIdle::new(drain_idle(&mut return_receiver), &self.return_sender);
+        log!(
+            self.log_levels.get,
+            "applying to {} idle objects",
+            idle.len()
+        );
+        let result = loop {
+            let Some(slot) = idle.front_mut() else {
+                break Ok(());
+            };
+            f(&mut slot.item).await;
+            if let Err(err) = idle.put_back_front() {
+                break Err(err);
+            }
+        };
+        // Puts back the objects not processed yet before failing.
+        drop(idle);
+        result
+    }
+
+    /**
+    Creates `samples` objects through the pool's factory, measuring how
+    long each takes, and drops them right away. The pool's objects and
+    slots are not touched. Failed creations are logged and still counted.
+
+    ```
+    # use futures::executor::block_on;
+    # use lazy_pool::Pool;
+
+    # struct AnyObject;
+
+    block_on(async {
+        let pool = Pool::new(1, Box::new(|| AnyObject)).await.unwrap();
+        let benchmark = pool.benchmark_factory(10).await;
+        assert!(benchmark.min <= benchmark.p95);
+    });
+    ```
+    */
+    pub async fn benchmark_factory(&self, samples: usize) -> FactoryBenchmark {
+        let mut factory = self.factory.lock().await;
+        let mut durations = Vec::with_capacity(samples);
+        for _ in 0..samples {
+            let start = Instant::now();
+            if let Err(err) = factory.try_produce().await {
+                warn!("failed to create object while benchmarking: {err:?}");
+            }
+            durations.push(start.elapsed());
+        }
+        FactoryBenchmark::from_samples(durations)
+    }
+
+    /**
+    Runs `ops` get/release cycles spread over `concurrency` tasks spawned
+    on the current Tokio runtime, measuring throughput and the longest
+    wait for an object, e.g. to catch performance regressions in
+    benchmarks and integration tests. Cycles which haven't finished after
+    30 seconds are abandoned and counted as errors. Requires the
+    `test-utils` feature.
+
+    ```
+    # use lazy_pool::Pool;
+
+    # struct AnyObject;
+
+    # #[tokio::main]
+    # async fn main() {
+    let pool = Pool::new(4, Box::new(|| AnyObject)).await.unwrap().into_shared();
+    let report = pool.stress_test(1000, 8).await;
+    assert_eq!(0, report.error_count);
+    # }
+    ```
+    */
+    #[cfg(feature = "test-utils")]
+    pub async fn stress_test(self: &Arc<Self>, ops: usize, concurrency: usize) -> StressReport {
+        let finished = Arc::new(AtomicUsize::new(0));
+        let failed = Arc::new(AtomicUsize::new(0));
+        let max_wait = Arc::new(AtomicU64::new(0));
+        let started = Instant::now();
+        let mut tasks = tokio::task::JoinSet::new();
+        for task_ops in stress::split_ops(ops, concurrency) {
+            let pool = self.clone();
+            let finished = finished.clone();
+            let failed = failed.clone();
+            let max_wait = max_wait.clone();
+            tasks.spawn(async move {
+                for _ in 0..task_ops {
+                    let waiting = Instant::now();
+                    let object = pool.get().await;
+                    let wait = waiting.elapsed().as_nanos() as u64;
+                    max_wait.fetch_max(wait, Ordering::Relaxed);
+                    if let Err(err) = object.release().await {
+                        warn!("failed to release object while stress testing: {err:?}");
+                        failed.fetch_add(1, Ordering::Relaxed);
+                    }
+                    finished.fetch_add(1, Ordering::Relaxed);
+                }
+            });
+        }
+        let mut timeout = Delay::new(stress::TIMEOUT).fuse();
+        loop {
+            select_biased! {
+                joined = tasks.join_next().fuse() => match joined {
+                    Some(Err(err)) => warn!("stress test task failed: {err:?}"),
+                    Some(Ok(())) => {}
+                    None => break,
+                },
+                _ = timeout => {
+                    warn!("stress test timed out");
+                    tasks.abort_all();
+                    break;
+                }
+            }
+        }
+        let elapsed = started.elapsed();
+        let finished = finished.load(Ordering::Relaxed);
+        let failed = failed.load(Ordering::Relaxed);
+        StressReport {
+            throughput_ops_per_sec: (finished - failed) as f64 / elapsed.as_secs_f64(),
+            max_wait: Duration::from_nanos(max_wait.load(Ordering::Relaxed)),
+            error_count: failed + (ops - finished),
+        }
+    }
+
+    /**
+    Takes up to `n` idle objects out of the pool without waiting. The pool
+    gives up their slots, so its capacity shrinks by the number of objects
+    returned, and the caller becomes responsible for cleaning them up.
+    Useful for closing connections gradually, e.g. during rolling restarts.
+    */
+    pub async fn take_idle_subset(&self, n: usize) -> Vec<T> {
+        // A checkout waiting on the receiver means there is nothing idle.
+        let Some(mut return_receiver) = self.return_receiver.try_lock() else {
+            return Vec::new();
+        };
+        let mut taken = Vec::new();
+        while taken.len() < n {
+            let Ok(slot) = return_receiver.try_recv() else {
+                break;
+            };
+            self.usage.reused();
+            taken.push(slot.item);
+        }
+        self.usage.retired(taken.len());
+        log!(self.log_levels.get, "took {} idle objects", taken.len());
+        taken
+    }
+
+    /**
+    Partitions the idle objects by `predicate` into `(matched, unmatched)`
+    and takes the matched ones out of the pool, e.g. to upgrade or replace
+    a subset of them, while the others stay in service. Unlike
+    [`Pool::take_idle_subset`], the pool keeps the slots of the objects
+    taken and creates replacements when needed.
+
+    The unmatched objects go back to the pool, so the second vector is
+    always empty; it is there for callers which expect both groups.
+
+    ```
+    # use futures::executor::block_on;
+    # use lazy_pool::Pool;
+
+    block_on(async {
+        let mut version = 0;
+        let pool = Pool::new_eager(3, move || {
+            version += 1;
+            version
+        })
+        .await
+        .unwrap();
+        let (outdated, unmatched) = pool.split_by(|version| *version < 3).await;
+        assert_eq!(vec![1, 2], outdated);
+        assert!(unmatched.is_empty());
+        assert_eq!(1, pool.stats().idle);
+    });
+    ```
+    */
+    pub async fn split_by<F>(&self, predicate: F) -> (Vec<T>, Vec<T>)
+    where
+        F: Fn(&T) -> bool,
+    {
+        let matched = self.evict_idle_where(|slot| predicate(&slot.item));
+        log!(
+            self.log_levels.get,
+            "split off {} idle objects",
+            matched.len()
+        );
+        (matched, Vec::new())
+    }
+
+    /**
+    Changes the number of objects the pool can hold. When shrinking, free
+    slots and idle objects are dropped first; objects which are checked out
+    are dropped when they are released, until the pool fits its new size.
+    */
+    pub fn resize(&self, size: usize) {
+        let capacity = self.stats().capacity;
+        log!(
+            self.log_levels.create,
+            "resizing pool from {capacity} to {size}"
+        );
+        if size >= capacity {
+            let added = size - capacity;
+            let slots = added - self.usage.cancel_retirements(added);
+            for _ in 0..slots {
+                if self.create_sender.unbounded_send(()).is_err() {
+                    warn!("failed to add slot to pool");
+                }
+            }
+            self.usage.grown(added);
+            return;
+        }
+        let mut remaining = capacity - size;
+        self.usage.retired(remaining);
+        if let Some(mut create_receiver) = self.create_receiver.try_lock() {
+            while remaining > 0 && create_receiver.try_recv().is_ok() {
+                remaining -= 1;
+            }
+        }
+        if let Some(mut return_receiver) = self.return_receiver.try_lock() {
+            while remaining > 0 {
+                let Ok(slot) = return_receiver.try_recv() else {
+                    break;
+                };
+                self.usage.reused();
+                self.callbacks.notify(PoolEvent::Evict, slot.id);
+                remaining -= 1;
+            }
+        }
+        self.usage.retire_later(remaining);
+    }
+
+    /**
+    Same as [`Pool::resize`], but resolves once the pool has converged to
+    its new size. Checked out objects are never taken away: when shrinking
+    below the number of objects in use, new checkouts wait while released
+    objects are dropped, until the objects in use fit the new size. Objects
+    released after that are pooled again.
+    */
+    pub async fn resize_preserving_checked_out(&self, size: usize) {
+        self.resize(size);
+        let mut in_use = self.usage.subscribe_in_use();
+        // Retirements are claimed before the object is checked in, so
+        // every one of them is followed by a change of `in_use`.
+        let _ = in_use
+            .wait_for(|_| self.usage.pending_retirements() == 0)
+            .await;
+        log!(self.log_levels.create, "pool converged to size {size}");
+    }
+
+    /**
+    Stops the pool from creating any more objects, e.g. once it is fully
+    populated with objects which each hold a unique license. Free slots
+    are removed, and objects which are dropped, e.g. because they are
+    tainted, are not replaced, so they permanently reduce the capacity.
+    Creating objects fails with [`LazyPoolError::NoFactory`] until a factory
+    is attached again through [`Pool::attach_factory`].
+    */
+    pub async fn detach_factory(&self) {
+        log!(self.log_levels.create, "detaching factory");
+        self.usage.set_detached(true);
+        *self.factory.lock().await = Box::new(NoFactory);
+        // A checkout holding the receiver means there are no free slots.
+        if let Some(mut create_receiver) = self.create_receiver.try_lock() {
+            while create_receiver.try_recv().is_ok() {
+                self.usage.retired(1);
+            }
+        }
+    }
+
+    /**
+    Sets the factory used to create objects, e.g. after
+    [`Pool::detach_factory`]. Objects which are dropped are replaced again
+    from then on; capacity lost while the factory was detached can be
+    restored through [`Pool::resize`].
+    */
+    pub async fn attach_factory<F>(&self, factory: F)
+    where
+        F: Factory<T> + 'static,
+    {
+        log!(self.log_levels.create, "attaching factory");
+        *self.factory.lock().await = Box::new(Infallible(factory));
+        self.usage.set_detached(false);
+    }
+
+    /**
+    Periodically resizes the pool as decided by `logic`, e.g. an
+    [`AutoScalePolicy`] or a closure taking [`PoolStats`]. Every `interval`
+    the logic is given the pool's [`AutoScaleSignals`], including the mean
+    wait of the checkouts made since the previous cycle. The returned
+    future never resolves, so it is meant to be spawned on the runtime of
+    choice next to the code using the pool.
+
+    ```no_run
+    # use lazy_pool::Pool;
+    # use std::{sync::Arc, time::Duration};
+
+    # struct AnyObject;
+
+    # #[tokio::main]
+    # async fn main() {
+    let pool = Arc::new(Pool::new(2, Box::new(|| AnyObject)).await.unwrap());
+    let scaled_pool = pool.clone();
+    tokio::spawn(async move {
+        scaled_pool
+            .auto_scale(Duration::from_secs(10), |stats: lazy_pool::PoolStats| {
+                (stats.idle == 0).then_some(stats.capacity + 1)
+            })
+            .await
+    });
+    # }
+    ```
+    */
+    pub async fn auto_scale<L>(&self, interval: Duration, logic: L)
+    where
+        L: AutoScaleLogic,
+    {
+        loop {
+            Delay::new(interval).await;
+            self.auto_scale_step(&logic, interval);
+        }
+    }
+
+    /**
+    Runs the scaling loop of [`Pool::auto_scale`], following `policy` every
+    `interval`. Never returns: the pool is only scaled while the returned
+    future is polled, so it is meant to be spawned next to the code using
+    the pool, and dropping it stops scaling.
+
+    ```no_run
+    # use lazy_pool::{AutoScalePolicy, Pool};
+    # use std::{sync::Arc, time::Duration};
+
+    # struct AnyObject;
+
+    # #[tokio::main]
+    # async fn main() {
+    let pool = Arc::new(Pool::new(2, Box::new(|| AnyObject)).await.unwrap());
+    let policy = AutoScalePolicy::new(
+        0.2,
+        Duration::from_millis(50),
+        Duration::from_secs(60),
+        2,
+        20,
+    )
+    .unwrap();
+    let scaled_pool = pool.clone();
+    tokio::spawn(async move {
+        scaled_pool
+            .run_auto_scale(Duration::from_secs(10), policy)
+            .await
+    });
+    # }
+    ```
+    */
+    #[must_use = "the pool is only scaled while the returned future is polled"]
+    pub async fn run_auto_scale(&self, interval: Duration, policy: AutoScalePolicy) {
+        self.auto_scale(interval, policy).await
+    }
+
+    fn auto_scale_step<L>(&self, logic: &L, interval: Duration)
+    where
+        L: AutoScaleLogic,
+    {
+        let signals = AutoScaleSignals {
+            stats: self.stats(),
+            mean_wait: Duration::from_secs_f64(
+                self.statistics_window(interval).mean_wait_ms / 1000.0,
+            ),
+            longest_idle: self
+                .inspect_idle(|idle| idle.iter().map(|slot| slot.idle_since.elapsed()).max())
+                .flatten(),
+        };
+        if let Some(size) = logic.target_size_from_signals(signals) {
+            self.resize(size);
+        }
+    }
+
+    /**
+    Changes how long objects may stay idle before [`Pool::evict_idle`]
+    drops them, e.g. from a configuration reload. Takes effect on the next
+    eviction cycle.
+    */
+    pub fn set_max_idle_duration(&self, duration: Duration) {
+        log!(
+            self.log_levels.create,
+            "setting max idle duration to {duration:?}"
+        );
+        *self.max_idle_duration.write().unwrap() = Some(duration);
+    }
+
+    /**
+    Drops objects which have been idle for longer than the duration set
+    through [`PoolBuilder::with_max_idle_duration`] or
+    [`Pool::set_max_idle_duration`], checking every `interval`. Their
+    slots are freed, so replacements are created lazily. The duration is
+    read anew on every cycle. Like [`Pool::auto_scale`], the returned
+    future never resolves and is meant to be spawned.
+
+    ```no_run
+    # use lazy_pool::{Pool, SyncFactory};
+    # use std::{sync::Arc, time::Duration};
+
+    # struct AnyObject;
+
+    # #[tokio::main]
+    # async fn main() {
+    let pool = Arc::new(
+        Pool::builder()
+            .factory(SyncFactory::from(|| AnyObject))
+            .with_max_idle_duration(Duration::from_secs(300))
+            .build()
+            .await
+            .unwrap(),
+    );
+    let evicting_pool = pool.clone();
+    tokio::spawn(async move { evicting_pool.evict_idle(Duration::from_secs(30)).await });
+    pool.set_max_idle_duration(Duration::from_secs(60));
+    # }
+    ```
+    */
+    pub async fn evict_idle(&self, interval: Duration) {
+        loop {
+            Delay::new(interval).await;
+            self.evict_idle_step().await;
+        }
+    }
+
+    async fn evict_idle_step(&self) {
+        let Some(max_idle_duration) = *self.max_idle_duration.read().unwrap() else {
+            return;
+        };
+        self.evict_idle_where(|slot| slot.idle_since.elapsed() >= max_idle_duration);
+    }
+
+    /**
+    Changes how often [`Pool::check_health`] checks the idle objects. A
+    shorter interval cuts the current wait short, while a longer one only
+    applies once the current wait is over.
+    */
+    pub fn set_health_check_interval(&self, interval: Duration) {
+        log!(
+            self.log_levels.create,
+            "setting health check interval to {interval:?}"
+        );
+        self.health_check_interval.send_replace(interval);
+    }
+
+    /**
+    Runs the validator set through [`PoolBuilder::with_return_validator`]
+    on the idle objects at the interval set through
+    [`PoolBuilder::with_health_check_interval`] or
+    [`Pool::set_health_check_interval`], dropping those which fail it.
+    Their slots are freed, so replacements are created lazily. Like
+    [`Pool::auto_scale`], the returned future never resolves and is meant
+    to be spawned.
+    */
+    pub async fn check_health(&self) {
+        let mut interval = self.health_check_interval.subscribe();
+        loop {
+            let mut period = *interval.borrow_and_update();
+            let last_check = Instant::now();
+            loop {
+                let remaining = period.saturating_sub(last_check.elapsed());
+                select_biased! {
+                    _ = Delay::new(remaining).fuse() => break,
+                    _ = interval.changed().fuse() => {
+                        period = period.min(*interval.borrow_and_update());
+                    }
+                }
+            }
+            self.check_health_step().await;
+        }
+    }
+
+    async fn check_health_step(&self) {
+        let Some(validator) = &self.return_validator else {
+            return;
+        };
+        let evicted = self.evict_idle_where(|slot| !validator(&slot.item));
+        for _ in evicted {
+            self.report_error(LazyPoolError::ValidationFailed);
+        }
+    }
+
+    /// Takes the idle objects matching `f` out of the pool and frees their slots.
+    fn evict_idle_where<F>(&self, f: F) -> Vec<T>
+    where
+        F: Fn(&Slot<T>) -> bool,
+    {
+        self.evict_idle_selected(|idle| idle.iter().map(f).collect())
+    }
+
+    /**
+    Same as [`Pool::evict_idle_where`], with `select` choosing which of the
+    idle objects, in the order they were put back, to take out of the pool.
+    */
+    fn evict_idle_selected<F>(&self, select: F) -> Vec<T>
+    where
+        F: FnOnce(&[Slot<T>]) -> Vec<bool>,
+    {
+        // A checkout waiting on the receiver means there is nothing idle.
+        let Some(mut return_receiver) = self.return_receiver.try_lock() else {
+            return Vec::new();
+        };
+        let idle = drain_idle(&mut return_receiver);
+        let selected = select(&idle);
+        let mut evicted = Vec::new();
+        for (slot, evict) in idle.into_iter().zip(selected) {
+            if !evict {
+                if self.return_sender.unbounded_send(slot).is_err() {
+                    warn!("failed to put back idle object");
+                }
+                continue;
+            }
+            self.usage.reused();
+            self.callbacks.notify(PoolEvent::Evict, slot.id);
+            log!(
+                self.log_levels.release,
+                "evicting idle object {id}",
+                id = slot.id
+            );
+            if self.create_sender.unbounded_send(()).is_err() {
+                warn!("failed to free slot of evicted object");
+            }
+            evicted.push(slot.item);
+        }
+        evicted
+    }
+
+    /**
+    Drops the `n` idle objects which were created the longest time ago,
+    keeping their slots so that replacements are created when needed, and
+    returns how many were dropped. For replacing objects gradually as they
+    age. Does nothing while a checkout is waiting for an object. See
+    [`PoolBuilder::with_idle_lru_eviction`] for dropping the least recently used
+    objects instead.
+    */
+    pub async fn evict_oldest_n(&self, n: usize) -> usize {
+        let evicted = self.evict_idle_selected(|idle| {
+            let mut by_age: Vec<_> = (0..idle.len()).collect();
+            if self.lru_eviction {
+                by_age.sort_by_key(|&index| idle[index].idle_since);
+            } else {
+                by_age.sort_by_key(|&index| idle[index].created_at);
+            }
+            let mut selected = vec![false; idle.len()];
+            for &index in by_age.iter().take(n) {
+                selected[index] = true;
+            }
+            selected
+        });
+        log!(
+            self.log_levels.release,
+            "evicted {} oldest idle objects",
+            evicted.len()
+        );
+        evicted.len()
+    }
+
+    /**
+    Shuts the pool down and hands back its idle objects. From then on
+    [`Pool::try_get`] returns `None` and [`Pool::get_timeout`] fails with
+    [`LazyPoolError::Closed`], while [`Pool::get`], including checkouts
+    waiting at the time of shutdown, never completes. Code which may still
+    check out objects after shutdown should use one of the fallible
+    checkouts instead. Objects which are checked out are dropped when
+    released. Calling this again returns no objects.
+
+    ```
+    # use futures::executor::block_on;
+    # use lazy_pool::Pool;
+
+    # struct AnyObject;
+
+    block_on(async {
+        let pool = Pool::new_eager(2, Box::new(|| AnyObject)).await.unwrap();
+        assert_eq!(2, pool.drain_and_shutdown().await.len());
+        assert!(pool.try_get().is_none());
+        assert!(pool.drain_and_shutdown().await.is_empty());
+    });
+    ```
+    */
+    pub async fn drain_and_shutdown(&self) -> Vec<T> {
+        if !self.usage.close() {
+            return Vec::new();
+        }
+        log!(self.log_levels.get, "shutting down pool");
+        // Wakes up waiting checkouts so that they release the receiver.
+        self.return_sender.close_channel();
+        let mut return_receiver = self.return_receiver.lock().await;
+        drain_idle(&mut return_receiver)
+            .into_iter()
+            .map(|slot| {
+                self.usage.reused();
+                self.callbacks.notify(PoolEvent::Evict, slot.id);
+                slot.item
+            })
+            .collect()
+    }
+
+    /**
+    Consumes the pool and yields all of its objects, e.g. to tear them down
+    in a defined order on shutdown. Idle objects are yielded first, objects
+    which haven't been created yet are created, and objects which are still
+    checked out are yielded once they are released. Yields nothing if the
+    pool has been shut down.
+
+    ```
+    # use futures::{executor::block_on, StreamExt};
+    # use lazy_pool::Pool;
+
+    block_on(async {
+        let pool = Pool::new(3, Box::new(|| 1)).await.unwrap();
+        let objects: Vec<_> = pool.into_async_iter().collect().await;
+        assert_eq!(vec![1, 1, 1], objects);
+    });
+    ```
+    */
+    pub fn into_async_iter(self) -> impl Stream<Item = T> {
+        let remaining = self.remaining_objects();
+        stream::unfold((self, remaining), |(pool, remaining)| async move {
+            if remaining == 0 {
+                return None;
+            }
+            let item = pool.take_next().await;
+            Some((item, (pool, remaining - 1)))
+        })
+    }
+
+    /**
+    Same as [`Pool::into_async_iter`], but blocks the current thread while
+    waiting for each object. Must not be used from within an async context.
+    `Pool` implements [`IntoIterator`] through this method.
+    */
+    pub fn into_blocking_iter(self) -> PoolBlockingIter<T> {
+        PoolBlockingIter {
+            remaining: self.remaining_objects(),
+            pool: self,
+        }
+    }
+
+    fn remaining_objects(&self) -> usize {
+        if self.usage.is_closed() {
+            0
+        } else {
+            self.stats().capacity
+        }
+    }
+
+    /// Takes the next object out of the pool for good, along with its slot.
+    pub(crate) async fn take_next(&self) -> T {
+        let slot = self.next_available().await;
+        self.usage.retired(1);
+        self.callbacks.notify(PoolEvent::Evict, slot.id);
+        slot.item
+    }
+
+    /**
+    Returns a [`PoolMonitor`] for registering callbacks on the lifecycle
+    events of this pool's objects.
+    */
+    pub fn monitor(&self) -> PoolMonitor {
+        PoolMonitor::new(self.callbacks.clone())
+    }
+
+    /**
+    Returns `false` once the factory failed at least the number of times
+    set through [`PoolBuilder::with_failure_threshold`] in a row. Any
+    successful creation makes the pool healthy again.
+    */
+    pub fn is_healthy(&self) -> bool {
+        self.health.is_healthy()
+    }
+
+    /// Same as [`Pool::is_healthy`] with some detail on the pool's state.
+    pub fn health_status(&self) -> HealthStatus {
+        if self.health.is_healthy() {
+            HealthStatus::Healthy {
+                idle: self.stats().idle,
+            }
+        } else {
+            HealthStatus::Degraded {
+                recent_failures: self.health.recent_failures(),
+            }
+        }
+    }
+
+    /**
+    Assembles a [`HealthReport`] about the pool's current state, e.g. for
+    a health check endpoint. Briefly takes the idle objects out of the
+    pool to find out how long the oldest of them has been waiting, unless
+    they are locked by a checkout waiting for an object, in which case the
+    age is reported as unavailable instead of waiting for the checkout.
+
+    ```
+    # use futures::executor::block_on;
+    # use lazy_pool::{Pool, SyncFactory};
+
+    # struct AnyObject;
+
+    block_on(async {
+        let pool = Pool::builder()
+            .name("db")
+            .size(2)
+            .factory(SyncFactory::from(|| AnyObject))
+            .build()
+            .await
+            .unwrap();
+        println!("{}", pool.health_report().await);
+    });
+    ```
+    */
+    pub async fn health_report(&self) -> HealthReport {
+        let oldest_idle = self
+            .inspect_idle(|idle| idle.iter().map(|slot| slot.idle_since.elapsed()).max())
+            .flatten();
+        let stats = self.stats();
+        HealthReport {
+            name: self.name().map(String::from),
+            capacity: stats.capacity,
+            idle: stats.idle,
+            in_use: stats.in_use,
+            total_creates: self.health.total_created(),
+            total_taints: self.usage.taints(),
+            recent_failures: self.health.recent_failures(),
+            status: self.health_status(),
+            oldest_idle,
+            mean_wait: self.activity.mean_wait(),
+        }
+    }
+
+    /**
+    Returns the ids of the objects currently idle in the pool, oldest
+    first, to correlate them with the object ids in log lines. This is a
+    snapshot, which misses objects released while it is taken. Empty
+    instead of waiting if the idle objects are locked by a checkout waiting
+    for an object.
+    */
+    pub async fn object_ids(&self) -> Vec<Uuid> {
+        self.inspect_idle(|idle| idle.iter().map(|slot| slot.id).collect())
+            .unwrap_or_default()
+    }
+
+    /**
+    Returns the ids of the objects currently checked out, longest held
+    first, e.g. to find out which objects are stuck when debugging a
+    slowdown. Together with [`Pool::object_ids`] this covers every object of
+    the pool. This is a snapshot, which may be outdated by the time it is
+    returned.
+    */
+    pub fn get_checked_out_ids(&self) -> Vec<Uuid> {
+        let mut checked_out: Vec<_> = self
+            .checked_out
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(id, since)| (*since, *id))
+            .collect();
+        checked_out.sort();
+        checked_out.into_iter().map(|(_, id)| id).collect()
+    }
+
+    /**
+    Briefly takes the idle objects out of the pool to inspect them. Gives
+    up if they are locked, e.g. by a checkout waiting for an object.
+    */
+    fn inspect_idle<F, R>(&self, f: F) -> Option<R>
+    where
+        F: FnOnce(&[Slot<T>]) -> R,
+    {
+        let mut return_receiver = self.return_receiver.try_lock()?;
+        let idle = drain_idle(&mut return_receiver);
+        let result = f(&idle);
+        for slot in idle {
+            if self.return_sender.unbounded_send(slot).is_err() {
+                warn!("failed to put back idle object");
+            }
+        }
+        Some(result)
+    }
+
+    /**
+    Resets the objects which are idle in the pool one after the other and
+    puts them back, returning how many were reset. Checkouts wait while the
+    objects are being reset. Objects which are checked out are not
+    affected. If this is cancelled, the objects which haven't been reset
+    are put back as they are.
+
+    Idle objects are locked by checkouts waiting for one, so resetting
+    starts only once no checkout is waiting. While the pool is exhausted
+    and checkouts wait, that means after an object has been released.
+    */
+    pub async fn reset_all_idle(&self) -> usize
+    where
+        T: Reset,
+    {
+        let mut return_receiver = self.return_receiver.lock().await;
+        let mut idle = TakenIdle::new(drain_idle(&mut return_receiver), &self.return_sender);
+        let count = idle.len();
+        while let Some(slot) = idle.front_mut() {
+            slot.item.reset().await;
+            if idle.put_back_front().is_err() {
+                warn!("failed to put back idle object");
+            }
+        }
+        log!(self.log_levels.release, "reset {count} idle objects");
+        count
+    }
+
+    /**
+    Checks the consistency of the pool's bookkeeping, returning a
+    description of each violation found. Meant for tests and test
+    harnesses: the checks take hold of the idle objects and free slots at
+    once, and are only reliable while no checkout or release is in
+    progress. Pools created through [`Pool::map`], [`Pool::filter_map`]
+    or [`Pool::pair`] share counters with each other, so the checks don't
+    apply to them. Waits for checkouts waiting for an object to get one
+    first, as they lock the idle objects and free slots, so don't call this
+    on an exhausted pool with checkouts waiting.
+    */
+    pub async fn validate_invariants(&self) -> StdResult<(), Vec<String>> {
+        let mut return_receiver = self.return_receiver.lock().await;
+        let mut create_receiver = self.create_receiver.lock().await;
+        let idle = drain_idle(&mut return_receiver);
+        let mut free_slots = 0;
+        while create_receiver.try_recv().is_ok() {
+            free_slots += 1;
+        }
+
+        let stats = self.stats();
+        let retiring = self.usage.pending_retirements();
+        let mut violations = Vec::new();
+        if idle.len() != stats.idle {
+            violations.push(format!(
+                "{} objects are idle, but the idle count is {}",
+                idle.len(),
+                stats.idle
+            ));
+        }
+        if idle.len() + stats.in_use + free_slots != stats.capacity + retiring {
+            violations.push(format!(
+                "{} idle and {} checked out objects and {free_slots} free slots don't add up \
+                 to the capacity of {} with {retiring} objects yet to be retired",
+                idle.len(),
+                stats.in_use,
+                stats.capacity
+            ));
+        }
+        let mut ids = HashSet::new();
+        for slot in &idle {
+            if !ids.insert(slot.id) {
+                violations.push(format!("object {} is idle more than once", slot.id));
+            }
+        }
+
+        for _ in 0..free_slots {
+            if self.create_sender.unbounded_send(()).is_err() {
+                warn!("failed to put back free slot");
+            }
+        }
+        for slot in idle {
+            if self.return_sender.unbounded_send(slot).is_err() {
+                warn!("failed to put back idle object");
+            }
+        }
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+
+    /**
+    Formats the pool's statistics in the Prometheus text exposition format,
+    for services which expose metrics without a metrics framework. Wait
+    quantiles are computed from the checkouts kept for
+    [`Pool::statistics_window`]. Samples are labelled with the pool's name.
+    */
+    pub fn export_prometheus(&self) -> String {
+        Metrics {
+            pool: self.name(),
+            stats: self.stats(),
+            total_creates: self.health.total_created(),
+            waits: self.activity.waits(),
+        }
+        .render()
+    }
+
+    /// Name of the pool as set through [`PoolBuilder::name`].
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /**
+    Returns rates of checkouts, creations and failures over the last
+    `window`. Computed from a buffer of the most recent events, whose size
+    is set through [`PoolBuilder::with_activity_buffer_size`], so windows
+    spanning more events than that only reflect the most recent ones.
+    */
+    pub fn statistics_window(&self, window: Duration) -> WindowedStats {
+        self.activity.window(window)
+    }
+
+    /// Returns a snapshot of the pool's capacity and usage.
+    pub fn stats(&self) -> PoolStats {
+        self.usage.snapshot()
+    }
+
+    /**
+    Total number of objects checked out of the pool since it was created.
+    Never decreases. Objects created through
+    [`Pool::get_or_create_overflow_with_limit`] beyond the pool size are
+    not counted.
+    */
+    pub fn get_count(&self) -> u64 {
+        self.usage.checkouts()
+    }
+
+    /**
+    Total number of checked out objects handed back since the pool was
+    created, whether they were returned to the pool or dropped because
+    they were tainted. Never decreases. `get_count() - return_count()` is
+    the number of objects currently in use, so a gap which keeps growing
+    points at objects which are held on to instead of released.
+    */
+    pub fn return_count(&self) -> u64 {
+        self.usage.checkins()
+    }
+
+    /**
+    Resolves the next time the pool becomes exhausted, i.e. the moment the
+    last free object is checked out. Useful for alerting and capacity
+    planning. Does not resolve right away if the pool is already exhausted.
+    */
+    pub async fn capacity_reached(&self) {
+        self.usage_edge(|stats| stats.in_use >= stats.capacity)
+            .await
+    }
+
+    /**
+    Resolves the next time an exhausted pool gets an object back, i.e. the
+    counterpart of [`Pool::capacity_reached`].
+    */
+    pub async fn capacity_available(&self) {
+        self.usage_edge(|stats| stats.in_use < stats.capacity).await
+    }
+
+    /// Waits for `condition` to turn from false to true as objects move.
+    async fn usage_edge<C>(&self, condition: C)
+    where
+        C: Fn(PoolStats) -> bool,
+    {
+        let mut in_use = self.usage.subscribe_in_use();
+        in_use.borrow_and_update();
+        let mut was_met = condition(self.stats());
+        // The sender is owned by the pool, which outlives this future.
+        while in_use.changed().await.is_ok() {
+            in_use.borrow_and_update();
+            let is_met = condition(self.stats());
+            if is_met && !was_met {
+                return;
+            }
+            was_met = is_met;
+        }
+    }
+
+    /**
+    Waits until every object which is checked out at the time of polling
+    has been released. New checkouts are not prevented, so under constant
+    load this may never resolve.
+
+    ```
+    # use futures::executor::block_on;
+    # use lazy_pool::Pool;
+
+    # struct AnyObject;
+
+    block_on(async {
+        let pool = Pool::new(2, Box::new(|| AnyObject)).await.unwrap();
+        let object = pool.get().await;
+        object.release().await.unwrap();
+        pool.checkpoint().await;
+    });
+    ```
+    */
+    pub async fn checkpoint(&self) {
+        let mut in_use = self.usage.subscribe_in_use();
+        // The sender is owned by the pool, which outlives this future.
+        let _ = in_use.wait_for(|in_use| *in_use == 0).await;
+    }
+
+    /**
+    Waits until no objects are checked out, e.g. as the last step of a
+    graceful shutdown after [`Pool::drain_and_shutdown`]. Resolves right
+    away if nothing is checked out. Same as [`Pool::checkpoint`].
+
+    ```
+    # use futures::executor::block_on;
+    # use lazy_pool::Pool;
+
+    # struct AnyObject;
+
+    block_on(async {
+        let pool = Pool::new(2, Box::new(|| AnyObject)).await.unwrap();
+        let object = pool.get().await;
+        pool.drain_and_shutdown().await;
+        object.release().await.unwrap();
+        pool.await_all_returned().await;
+    });
+    ```
+    */
+    pub async fn await_all_returned(&self) {
+        self.checkpoint().await
+    }
+}
+
+/// Takes all objects currently waiting in the return channel, oldest first.
+fn drain_idle<T>(return_receiver: &mut mpsc::UnboundedReceiver<Slot<T>>) -> Vec<Slot<T>> {
+    let mut idle = Vec::new();
+    while let Ok(slot) = return_receiver.try_recv() {
+        idle.push(slot);
+    }
+    idle
+}
+
+/**
+Idle objects taken out of the pool to be processed one after the other.
+The objects which haven't been put back yet are put back when this is
+dropped, so that they aren't lost if processing is cancelled.
+*/
+struct TakenIdle<'a, T> {
+    slots: VecDeque<Slot<T>>,
+    return_sender: &'a mpsc::UnboundedSender<Slot<T>>,
+}
+
+impl<'a, T> TakenIdle<'a, T> {
+    fn new(slots: Vec<Slot<T>>, return_sender: &'a mpsc::UnboundedSender<Slot<T>>) -> Self {
+        Self {
+            slots: slots.into(),
+            return_sender,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    fn front_mut(&mut self) -> Option<&mut Slot<T>> {
+        self.slots.front_mut()
+    }
+
+    fn put_back_front(&mut self) -> Result<()> {
+        let Some(slot) = self.slots.pop_front() else {
+            return Ok(());
+        };
+        self.return_sender
+            .unbounded_send(slot)
+            .map_err(|err| LazyPoolError::Send(err.into_send_error()))
+    }
+}
+
+impl<T> Drop for TakenIdle<'_, T> {
+    fn drop(&mut self) {
+        for slot in self.slots.drain(..) {
+            if self.return_sender.unbounded_send(slot).is_err() {
+                warn!("failed to put back idle object");
+            }
+        }
+    }
+}
+
+/// Puts back an object handed to a priority checkout which gave up waiting.
+struct WaiterGuard<'a, T: Send + 'static> {
+    receiver: oneshot::Receiver<Slot<T>>,
+    pool: &'a Pool<T>,
+}
+
+impl<T: Send> Drop for WaiterGuard<'_, T> {
+    fn drop(&mut self) {
+        self.receiver.close();
+        if let Ok(Some(slot)) = self.receiver.try_recv() {
+            self.pool.usage.returning();
+            if self.pool.return_sender.unbounded_send(slot).is_err() {
+                self.pool.usage.reused();
+                warn!("failed to put back object of cancelled checkout");
+            }
+        }
+    }
+}
+
+struct CreateGuard(Option<mpsc::UnboundedSender<()>>);
+
+impl Drop for CreateGuard {
+    fn drop(&mut self) {
+        if let Some(sender) = self.0.take() {
+            if sender.unbounded_send(()).is_err() {
+                warn!("failed to return slot of cancelled creation");
+            }
+        }
+    }
+}
+
+pub struct Pooled<T: Send + 'static> {
+    wrapped: Option<Slot<T>>,
+    pool_name: Option<Arc<str>>,
+    tainted: bool,
+    checkout_id: u64,
+    release_level: Level,
+    usage: Arc<Usage>,
+    return_validator: Option<Validator<T>>,
+    post_return_hook: Option<Hook<T>>,
+    shrink_to: Option<usize>,
+    callbacks: Arc<Callbacks>,
+    overflow_count: Option<Arc<AtomicUsize>>,
+    waiters: Arc<Waiters<Slot<T>>>,
+    return_sender: mpsc::UnboundedSender<Slot<T>>,
+    create_sender: mpsc::UnboundedSender<()>,
+    error_handler: Option<ErrorHandler>,
+    checked_out: Arc<SyncMutex<HashMap<Uuid, Instant>>>,
+    holder: Option<HolderGuard>,
+    observer: Option<Box<dyn PoolObserver<T>>>,
+    on_return: Option<ReturnCallback<T>>,
+    telemetry: Option<CheckoutTelemetry>,
+    #[cfg(feature = "opentelemetry")]
+    span: Option<opentelemetry::global::BoxedSpan>,
+}
+
+impl<T: Send> Pooled<T> {
+    pub fn tainted(&mut self) {
+        self.tainted = true;
+    }
+
+    /**
+    Sequential id of this checkout. Every successful `get` on a pool is
+    assigned the next id, so ids reflect the order in which objects were
+    handed out and can be used to correlate log lines.
+    */
+    pub fn checkout_id(&self) -> u64 {
+        self.checkout_id
+    }
+
+    /**
+    Unique id of the pooled object. Unlike the checkout id, it stays the
+    same for as long as the object lives in the pool.
+    */
+    pub fn object_id(&self) -> Uuid {
+        self.wrapped.as_ref().unwrap().id
+    }
+
+    pub async fn release(mut self) -> Result<()> {
+        self.return_to_pool()
+    }
+
+    /// Releases the object without consuming the handle, e.g. from `drop`.
+    pub(crate) fn return_to_pool(&mut self) -> Result<()> {
+        let Some(slot) = self.wrapped.take() else {
+            warn!("release called multiple times");
+            return Ok(());
+        };
+        let hooks = ReleaseHooks {
+            overflow_count: self.overflow_count.take(),
+            observer: self.observer.take(),
+            on_return: self.on_return.take(),
+        };
+        let releaser = Releaser {
+            pool_name: &self.pool_name,
+            release_level: self.release_level,
+            usage: &self.usage,
+            return_validator: self.return_validator.as_ref(),
+            post_return_hook: self.post_return_hook.as_ref(),
+            shrink_to: self.shrink_to,
+            callbacks: &self.callbacks,
+            waiters: &self.waiters,
+            return_sender: &self.return_sender,
+            create_sender: &self.create_sender,
+            error_handler: self.error_handler.as_ref(),
+            checked_out: &self.checked_out,
+        };
+        releaser.release(slot, self.tainted, self.checkout_id, hooks)
+    }
+}
+
+/// Per-checkout handling of a released object, on top of the pool's own.
+struct ReleaseHooks<T> {
+    overflow_count: Option<Arc<AtomicUsize>>,
+    observer: Option<Box<dyn PoolObserver<T>>>,
+    on_return: Option<ReturnCallback<T>>,
+}
+
+impl<T> Default for ReleaseHooks<T> {
+    fn default() -> Self {
+        Self {
+            overflow_count: None,
+            observer: None,
+            on_return: None,
+        }
+    }
+}
+
+/**
+Puts released objects back into the pool. Borrows the pool's handles from
+either the pool itself or the clones held by a [`Pooled`], so that objects
+checked out with and without handles are released the same way.
+*/
+struct Releaser<'a, T: Send + 'static> {
+    pool_name: &'a Option<Arc<str>>,
+    release_level: Level,
+    usage: &'a Usage,
+    return_validator: Option<&'a Validator<T>>,
+    post_return_hook: Option<&'a Hook<T>>,
+    shrink_to: Option<usize>,
+    callbacks: &'a Callbacks,
+    waiters: &'a Waiters<Slot<T>>,
+    return_sender: &'a mpsc::UnboundedSender<Slot<T>>,
+    create_sender: &'a mpsc::UnboundedSender<()>,
+    error_handler: Option<&'a ErrorHandler>,
+    checked_out: &'a SyncMutex<HashMap<Uuid, Instant>>,
+}
+
+impl<T: Send> Releaser<'_, T> {
+    fn release(
+        &self,
+        mut slot: Slot<T>,
+        mut tainted: bool,
+        checkout_id: u64,
+        hooks: ReleaseHooks<T>,
+    ) -> Result<()> {
+        let release_level = self.release_level;
+        self.checked_out.lock().unwrap().remove(&slot.id);
+        if let Some(overflow_count) = hooks.overflow_count {
+            log!(release_level, "dropping overflow object {}", slot.id);
+            self.callbacks.notify(PoolEvent::Evict, slot.id);
+            overflow_count.fetch_sub(1, Ordering::SeqCst);
+            return Ok(());
+        }
+        if let Some(validator) = self.return_validator {
+            if !tainted && !validator(&slot.item) {
+                log!(release_level, "object failed return validation");
+                tainted = true;
+            }
+        }
+        if let Some(observer) = hooks.observer {
+            observer.on_return(&slot.item, tainted);
+        }
+        if let Some(on_return) = hooks.on_return {
+            on_return(&slot.item);
+        }
+        if let Some(min) = self.shrink_to {
+            let stats = self.usage.snapshot();
+            if !tainted && stats.idle + stats.in_use > min && stats.idle >= min {
+                log!(release_level, "dropping object to shrink pool");
+                tainted = true;
+            }
+        }
+        if tainted {
+            self.usage.tainted();
+        }
+        log!(
+            release_level,
+            "releasing object (checkout_id = {checkout_id}, tainted = {tainted})"
+        );
+        let context = PoolContext {
+            pool_name: self.pool_name.clone(),
+            object_id: Some(slot.id),
+        };
+        let result = if self.usage.is_closed() || self.usage.take_retirement() {
+            log!(release_level, "dropping object of shrinking or closed pool");
+            self.callbacks.notify(PoolEvent::Evict, slot.id);
+            Ok(())
+        } else if tainted && self.usage.is_detached() {
+            log!(
+                release_level,
+                "dropping object for good, factory is detached"
+            );
+            self.callbacks.notify(PoolEvent::Evict, slot.id);
+            self.usage.retired(1);
+            Ok(())
+        } else if tainted {
+            self.callbacks.notify(PoolEvent::Evict, slot.id);
+            self.create_sender.unbounded_send(()).map_err(|_| ())
+        } else {
+            if let Some(hook) = self.post_return_hook {
+                hook(&slot.item);
+            }
+            self.callbacks.notify(PoolEvent::Return, slot.id);
+            slot.idle_since = Instant::now();
+            slot.fresh = false;
+            let Err(slot) = self.waiters.serve(slot) else {
+                self.usage.checked_in();
+                return Ok(());
+            };
+            self.usage.returning();
+            let result = self.return_sender.unbounded_send(slot);
+            if result.is_err() {
+                self.usage.reused();
+            }
+            result.map_err(|_| ())
+        };
+        self.usage.checked_in();
+        if let (Err(()), Some(handler)) = (result, self.error_handler) {
+            handler(LazyPoolError::Release {
+                context: context.clone(),
+            });
+        }
+        result.map_err(|_| LazyPoolError::Release { context })
+    }
+}
+
+impl<T: Send + Display> Display for Pooled<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.wrapped {
+            Some(slot) => slot.item.fmt(f),
+            None => write!(f, "Pooled(released)"),
+        }
+    }
+}
+
+impl<T: Send> Drop for Pooled<T> {
+    fn drop(&mut self) {
+        if self.wrapped.is_none() {
+            return;
+        }
+        if let Err(err) = self.return_to_pool() {
+            log::error!("failed to release dropped object: {err:?}");
+        }
+    }
+}
+
+impl<T: Send + Future + Unpin> Future for Pooled<T> {
+    type Output = T::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T::Output> {
+        Pin::new(&mut **self.get_mut()).poll(cx)
+    }
+}
+
+impl<T: Send + Debug> Debug for Pooled<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.wrapped {
+            Some(slot) => write!(f, "Pooled(tainted={}, value={:?})", self.tainted, slot.item),
+            None => write!(f, "Pooled(released)"),
+        }
+    }
+}
+
+impl<T: Send + io::Read> io::Read for Pooled<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        (**self).read(buf)
+    }
+}
+
+impl<T: Send + io::Write> io::Write for Pooled<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        (**self).write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        (**self).flush()
+    }
+}
+
+impl<T: Send + AsyncRead + Unpin> AsyncRead for Pooled<T> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut **self.get_mut()).poll_read(cx, buf)
+    }
+}
+
+impl<T: Send + AsyncWrite + Unpin> AsyncWrite for Pooled<T> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut **self.get_mut()).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut **self.get_mut()).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut **self.get_mut()).poll_shutdown(cx)
+    }
+}
+
+impl<T: Send> DerefMut for Pooled<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.wrapped.as_mut().unwrap().item
+    }
+}
+
+impl<T: Send> Deref for Pooled<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.wrapped.as_ref().unwrap().item
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    extern crate uuid;
+
+    use super::*;
+
+    use futures::{executor::block_on, poll, select, Future};
+    use log::debug;
+    use std::{
+        collections::{HashMap, HashSet},
+        iter::FromIterator,
+        result::Result as StdResult,
+        thread,
+    };
+    use test_log::test;
+    use tokio::task::JoinSet;
+
+    #[derive(PartialEq, Debug, Clone)]
+    struct AnyObject {
+        member: String,
+    }
+
+    impl AnyObject {
+        fn new() -> Self {
+            AnyObject {
+                member: uuid::Uuid::new_v4().to_string(),
+            }
+        }
+
+        fn with_context(context: &str) -> Self {
+            AnyObject {
+                member: String::from(context),
+            }
+        }
+    }
+
+    #[test(tokio::test)]
+    async fn can_get_object_from_pool() {
+        let pool = Pool::new(
+            10,
+            Box::new(|| AnyObject {
+                member: String::from("member"),
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
             AnyObject {
                 member: String::from("member")
             },
-            get!(item = pool => (*item).clone())
+            get!(item = pool => (*item).clone())
+        )
+    }
+
+    #[test(tokio::test)]
+    async fn can_get_multiple_objects_from_pool() {
+        let pool = Pool::new(10, Box::new(AnyObject::new)).await.unwrap();
+        get!(item_1 = pool => {
+            get!(item_2 = pool => {
+                assert!(*item_1 != *item_2);
+            })
+        })
+    }
+
+    #[test(tokio::test)]
+    async fn item_is_relased_back_to_the_start_of_the_pool_when_dropped() {
+        let pool = Pool::new(10, Box::new(AnyObject::new)).await.unwrap();
+        let member_name_1 = get!(item = pool => item.member.clone());
+        let member_name_2 = get!(item = pool => item.member.clone());
+        let member_name_3 = get!(item = pool => item.member.clone());
+        assert_eq!(member_name_1, member_name_2);
+        assert_eq!(member_name_2, member_name_3);
+    }
+
+    #[test(tokio::test)]
+    async fn pool_does_not_go_over_capacity_when_running_out_of_items() {
+        let pool = Pool::new(2, Box::new(AnyObject::new)).await.unwrap();
+        get!(item_1 = pool => {
+            get!(item_2 = pool => {
+                let mut timeout = Delay::new(Duration::from_millis(100)).fuse();
+                select! {
+                    _ = pool.get().fuse() => panic!("should not be able to get this"),
+                    _ = timeout => {}
+                }
+            });
+            let mut timeout = Delay::new(Duration::from_millis(100)).fuse();
+            select! {
+                _ = pool.get().fuse() => {},
+                _ = timeout => panic!("should be able to get this"),
+            }
+        });
+    }
+
+    #[test]
+    #[allow(clippy::redundant_closure)]
+    fn can_share_pool_between_threads_in_sync_code() {
+        let pool = Arc::new(block_on(async {
+            Pool::new(3, Box::new(|| AnyObject::new())).await.unwrap()
+        }));
+        let members = Arc::new(SyncMutex::new(HashSet::<String>::new()));
+        let mut handles = vec![];
+
+        for _ in 1..10 {
+            let local_pool = pool.clone();
+            let local_members = members.clone();
+            let handle = thread::spawn(move || {
+                let value = block_on(async {
+                    get!(item = local_pool => {
+                        Delay::new(Duration::from_millis(100)).await;
+                        item.member.clone()
+                    })
+                });
+                debug!("adding value to members");
+                local_members.lock().unwrap().insert(value);
+            });
+            handles.push(handle);
+        }
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(members.lock().unwrap().len(), 3);
+    }
+
+    #[test(tokio::test)]
+    async fn can_use_closure_as_factory() {
+        let context = "hello";
+        let pool = Pool::new(10, Box::new(move || AnyObject::with_context(context)))
+            .await
+            .unwrap();
+        assert_eq!(
+            AnyObject {
+                member: String::from("hello")
+            },
+            get!(item = pool => item.clone())
+        )
+    }
+
+    struct AsyncFactory {}
+
+    impl AsyncFactory {
+        async fn get_instance(&self) -> AnyObject {
+            AnyObject {
+                member: String::from("hello"),
+            }
+        }
+    }
+
+    impl Factory<AnyObject> for AsyncFactory {
+        fn produce(&mut self) -> Box<dyn Future<Output = AnyObject> + Send + Unpin + '_> {
+            Box::new(Box::pin(self.get_instance()))
+        }
+    }
+
+    #[test(tokio::test)]
+    async fn can_use_async_function_as_factory() {
+        let pool = Pool::new_with_factory(10, AsyncFactory {}).await.unwrap();
+        assert_eq!(
+            AnyObject {
+                member: String::from("hello")
+            },
+            get!(item = pool => item.clone())
+        )
+    }
+
+    struct AsyncPoolHolder {
+        pool: Pool<AnyObject>,
+    }
+
+    impl AsyncPoolHolder {
+        #[allow(clippy::redundant_closure)]
+        async fn new() -> Self {
+            Self {
+                pool: Pool::new(3, Box::new(|| AnyObject::new())).await.unwrap(),
+            }
+        }
+
+        async fn get_member_value(self) -> Vec<String> {
+            let mut values = vec![];
+            get!(object_1 = self.pool => {
+                get!(object_2 = self.pool => {
+                    get!(object_3 = self.pool => {
+                        values.push(object_1.member.clone());
+                        values.push(object_2.member.clone());
+                        values.push(object_3.member.clone());
+                    })
+                })
+            });
+            get!(object_4 = self.pool => {
+                get!(object_5 = self.pool => {
+                    get!(object_6 = self.pool => {
+                        values.push(object_4.member.clone());
+                        values.push(object_5.member.clone());
+                        values.push(object_6.member.clone());
+                    })
+                })
+            });
+            values
+        }
+    }
+
+    #[test(tokio::test)]
+    async fn can_run_in_async() {
+        let holder = AsyncPoolHolder::new().await;
+        let res = holder.get_member_value().await;
+        let set: HashSet<String> = HashSet::from_iter(res.iter().cloned());
+        assert_eq!(3, set.len())
+    }
+
+    #[test(tokio::test)]
+    async fn can_mutate_polled_items() {
+        struct Mutable {
+            count: i32,
+        }
+
+        let pool = Pool::new(2, Box::new(|| Mutable { count: 0 }))
+            .await
+            .unwrap();
+
+        get!(object = pool => {
+            object.count += 1;
+        });
+
+        get!(item_1 = pool => {
+            get!(item_2 = pool => {
+                assert_eq!(1, item_1.count);
+                assert_eq!(0, item_2.count);
+            })
+        })
+    }
+
+    #[test(tokio::test)]
+    async fn marking_item_as_tainted_drops_it_from_pool() {
+        let pool = Pool::new(1, Box::new(AnyObject::new)).await.unwrap();
+
+        let mut item_val = String::new();
+        get!(item = pool => {
+            item_val += &item.member;
+        });
+        get!(item = pool => {
+            assert!(item_val == item.member);
+            Pooled::tainted(&mut item);
+        });
+        get!(item = pool => {
+            assert!(item_val != item.member);
+        });
+    }
+
+    #[test(tokio::test)]
+    async fn can_run_in_multi_task_mode() {
+        let pool = Arc::new(Pool::new(1, Box::new(AnyObject::new)).await.unwrap());
+        let mut join_set = JoinSet::new();
+        for _ in 0..10 {
+            let local_pool = pool.clone();
+            join_set.spawn(async move {
+                get!(item = local_pool => {
+                    println!("{}", item.member);
+                });
+            });
+        }
+        while !join_set.is_empty() {
+            join_set.join_next().await;
+        }
+    }
+
+    #[test(tokio::test)]
+    async fn pool_operations_log_at_configured_levels() {
+        let pool = Pool::builder()
+            .size(1)
+            .factory(SyncFactory::from(AnyObject::new))
+            .with_log_level(Level::Trace, Level::Info, Level::Warn)
+            .build()
+            .await
+            .unwrap();
+        assert_eq!(Level::Trace, pool.log_levels.get);
+        let release_level = get!(item = pool => item.release_level);
+        assert_eq!(Level::Info, release_level);
+        assert_eq!(Level::Warn, pool.log_levels.create);
+    }
+
+    #[test(tokio::test)]
+    async fn pool_operations_log_at_debug_by_default() {
+        let pool = Pool::new(1, Box::new(AnyObject::new)).await.unwrap();
+        assert_eq!(LogLevels::default(), pool.log_levels);
+        assert_eq!(Level::Debug, pool.log_levels.get);
+    }
+
+    #[test(tokio::test)]
+    async fn builder_requires_a_factory() {
+        let result = PoolBuilder::<AnyObject>::new().size(1).build().await;
+        assert!(matches!(result, Err(LazyPoolError::MissingFactory)));
+    }
+
+    #[test(tokio::test)]
+    async fn stats_track_idle_and_in_use_objects() {
+        let pool = Pool::new(3, Box::new(AnyObject::new)).await.unwrap();
+        assert_eq!(
+            PoolStats {
+                capacity: 3,
+                idle: 0,
+                in_use: 0
+            },
+            pool.stats()
+        );
+        get!(item_1 = pool => {
+            get!(item_2 = pool => {
+                assert_eq!(2, pool.stats().in_use);
+                Pooled::tainted(&mut item_2);
+            });
+            assert_eq!(1, pool.stats().in_use);
+            assert_eq!(0, pool.stats().idle);
+        });
+        assert_eq!(0, pool.stats().in_use);
+        assert_eq!(1, pool.stats().idle);
+    }
+
+    #[test(tokio::test)]
+    async fn checkpoint_resolves_when_all_objects_are_released() {
+        let pool = Pool::new(3, Box::new(AnyObject::new)).await.unwrap();
+        let item_1 = pool.get().await;
+        let item_2 = pool.get().await;
+        let mut checkpoint = Box::pin(pool.checkpoint());
+        assert!(poll!(checkpoint.as_mut()).is_pending());
+        item_1.release().await.unwrap();
+        assert!(poll!(checkpoint.as_mut()).is_pending());
+        item_2.release().await.unwrap();
+        assert!(poll!(checkpoint.as_mut()).is_ready());
+    }
+
+    #[test(tokio::test)]
+    async fn get_timeout_fails_when_pool_is_exhausted() {
+        let pool = Pool::new(1, Box::new(AnyObject::new)).await.unwrap();
+        get!(item = pool => {
+            let result = pool.get_timeout(Duration::from_millis(50)).await;
+            assert!(matches!(result, Err(LazyPoolError::Timeout)));
+            let deadline = Instant::now() + Duration::from_millis(50);
+            let result = pool.get_with_deadline(deadline).await;
+            assert!(matches!(result, Err(LazyPoolError::Timeout)));
+        });
+        assert!(pool.get_timeout(Duration::from_millis(50)).await.is_ok());
+    }
+
+    struct SlowFactory {}
+
+    impl Factory<AnyObject> for SlowFactory {
+        fn produce(&mut self) -> Box<dyn Future<Output = AnyObject> + Send + Unpin + '_> {
+            Box::new(Box::pin(async {
+                Delay::new(Duration::from_millis(100)).await;
+                AnyObject::new()
+            }))
+        }
+    }
+
+    #[test(tokio::test)]
+    async fn timing_out_during_creation_does_not_lose_the_slot() {
+        let pool = Pool::new_with_factory(1, SlowFactory {}).await.unwrap();
+        let result = pool.get_timeout(Duration::from_millis(10)).await;
+        assert!(matches!(result, Err(LazyPoolError::Timeout)));
+        assert!(pool.get_timeout(Duration::from_millis(500)).await.is_ok());
+    }
+
+    #[test(tokio::test)]
+    async fn get_with_deadline_macro_accepts_durations_and_instants() {
+        let pool = Pool::new(1, Box::new(AnyObject::new)).await.unwrap();
+        let member = get_with_deadline!(item = pool, Duration::from_millis(50) => {
+            item.member.clone()
+        })
+        .unwrap();
+        let deadline = Instant::now() + Duration::from_millis(50);
+        let same_member = get_with_deadline!(item = pool, deadline => item.member.clone()).unwrap();
+        assert_eq!(member, same_member);
+
+        let mut ran = false;
+        get!(_item = pool => {
+            let result = get_with_deadline!(_item = pool, Duration::from_millis(10) => {
+                ran = true;
+            });
+            assert!(matches!(result, Err(LazyPoolError::Timeout)));
+            let deadline = Instant::now() + Duration::from_millis(10);
+            let result = get_with_deadline!(_item = pool, deadline => {
+                ran = true;
+            });
+            assert!(matches!(result, Err(LazyPoolError::Timeout)));
+        });
+        assert!(!ran);
+    }
+
+    #[test(tokio::test)]
+    async fn objects_failing_return_validation_are_not_recycled() {
+        let pool = Pool::builder()
+            .size(1)
+            .factory(SyncFactory::from(AnyObject::new))
+            .with_return_validator(|item: &AnyObject| item.member != "invalid")
+            .build()
+            .await
+            .unwrap();
+        let member = get!(item = pool => item.member.clone());
+        get!(item = pool => {
+            assert_eq!(member, item.member);
+            item.member = String::from("invalid");
+        });
+        get!(item = pool => {
+            assert_ne!(member, item.member);
+            assert_ne!("invalid", item.member);
+        });
+    }
+
+    #[test(tokio::test)]
+    async fn mapped_pool_produces_mapped_objects() {
+        let pool = Pool::new(2, Box::new(|| String::from("hello")))
+            .await
+            .unwrap();
+        let lengths = pool.map(|value| value.len());
+        assert_eq!(5, get!(length = lengths => *length));
+    }
+
+    #[test(tokio::test)]
+    async fn mapped_pool_shares_capacity_with_original_pool() {
+        let pool = Pool::new(1, Box::new(AnyObject::new)).await.unwrap();
+        let members = pool.map(|item| item.member);
+        get!(item = pool => {
+            let result = members.get_timeout(Duration::from_millis(50)).await;
+            assert!(matches!(result, Err(LazyPoolError::Timeout)));
+            Pooled::tainted(&mut item);
+        });
+        get!(member = members => {
+            assert_eq!(1, pool.stats().in_use);
+            let result = pool.get_timeout(Duration::from_millis(50)).await;
+            assert!(matches!(result, Err(LazyPoolError::Timeout)));
+            Pooled::tainted(&mut member);
+        });
+        assert!(pool.get_timeout(Duration::from_millis(50)).await.is_ok());
+    }
+
+    #[test(tokio::test)]
+    async fn filter_mapped_pool_recreates_rejected_objects() {
+        let counter = Arc::new(AtomicU64::new(0));
+        let factory_counter = counter.clone();
+        let pool = Pool::new(1, move || factory_counter.fetch_add(1, Ordering::SeqCst))
+            .await
+            .unwrap();
+        let odd = pool.filter_map(|value| (value % 2 == 1).then_some(value));
+        assert_eq!(1, get!(value = odd => *value));
+        assert_eq!(2, counter.load(Ordering::SeqCst));
+    }
+
+    #[test(tokio::test)]
+    async fn filter_mapped_pool_gives_up_after_max_create_retries() {
+        let counter = Arc::new(AtomicU64::new(0));
+        let factory_counter = counter.clone();
+        let pool = Pool::builder()
+            .size(1)
+            .factory(SyncFactory::from(move || {
+                factory_counter.fetch_add(1, Ordering::SeqCst)
+            }))
+            .with_max_create_retries(2)
+            .build()
+            .await
+            .unwrap();
+        let large = pool.filter_map(|value| (value >= 5).then_some(value));
+        // The first three objects exhaust the retries of the first creation
+        // attempt, the checkout then tries again with a new creation.
+        assert_eq!(5, get!(value = large => *value));
+        assert_eq!(6, counter.load(Ordering::SeqCst));
+    }
+
+    struct FailingFactory {
+        failures: usize,
+    }
+
+    impl TryFactory<AnyObject> for FailingFactory {
+        fn try_produce(
+            &mut self,
+        ) -> Box<dyn Future<Output = StdResult<AnyObject, FactoryError>> + Send + Unpin + '_>
+        {
+            let result = if self.failures > 0 {
+                self.failures -= 1;
+                Err("factory failure".into())
+            } else {
+                Ok(AnyObject::new())
+            };
+            Box::new(std::future::ready(result))
+        }
+    }
+
+    #[test(tokio::test)]
+    async fn checkouts_retry_failed_creations() {
+        let pool = Pool::new_with_try_factory(1, FailingFactory { failures: 3 })
+            .await
+            .unwrap();
+        get!(item = pool => assert!(!item.member.is_empty()));
+        assert_eq!(1, pool.stats().idle);
+    }
+
+    #[test(tokio::test)]
+    async fn shared_pool_can_be_used_from_multiple_tasks() {
+        let pool = Pool::new(1, Box::new(AnyObject::new))
+            .await
+            .unwrap()
+            .into_shared();
+        let mut join_set = JoinSet::new();
+        for _ in 0..5 {
+            let local_pool = pool.clone();
+            join_set.spawn(async move { get!(item = local_pool => item.member.clone()) });
+        }
+        let mut members = HashSet::new();
+        while let Some(member) = join_set.join_next().await {
+            members.insert(member.unwrap());
+        }
+        assert_eq!(1, members.len());
+    }
+
+    #[test(tokio::test)]
+    async fn eager_pool_creates_all_objects_upfront() {
+        let counter = Arc::new(AtomicU64::new(0));
+        let factory_counter = counter.clone();
+        let pool = Pool::new_eager(5, move || {
+            factory_counter.fetch_add(1, Ordering::SeqCst);
+            AnyObject::new()
+        })
+        .await
+        .unwrap();
+        assert_eq!(5, pool.stats().idle);
+        assert_eq!(5, counter.load(Ordering::SeqCst));
+        get!(_item = pool => assert_eq!(4, pool.stats().idle));
+        assert_eq!(5, counter.load(Ordering::SeqCst));
+    }
+
+    #[test(tokio::test)]
+    async fn warming_fails_when_the_factory_fails() {
+        let pool = Pool::new_with_try_factory(3, FailingFactory { failures: 1 })
+            .await
+            .unwrap();
+        let result = pool.warm(3).await;
+        assert!(matches!(result, Err(LazyPoolError::Factory(_))));
+        pool.warm(3).await.unwrap();
+        assert_eq!(3, pool.stats().idle);
+    }
+
+    #[test(tokio::test)]
+    async fn pool_keeps_minimum_idle_objects() {
+        let pool = Pool::new_with_min_idle(5, 2, AnyObject::new).await.unwrap();
+        assert_eq!(2, pool.stats().idle);
+        get!(_item_1 = pool => {
+            assert_eq!(2, pool.stats().idle);
+            get!(_item_2 = pool => {
+                assert_eq!(2, pool.stats().idle);
+            });
+        });
+        assert_eq!(4, pool.stats().idle);
+        for _ in 0..10 {
+            get!(_item = pool => assert!(pool.stats().idle >= 2));
+        }
+    }
+
+    #[test(tokio::test)]
+    async fn pool_does_not_replenish_beyond_capacity() {
+        let pool = Pool::new_with_min_idle(2, 2, AnyObject::new).await.unwrap();
+        get!(_item_1 = pool => {
+            get!(_item_2 = pool => {
+                assert_eq!(0, pool.stats().idle);
+            });
+        });
+        assert_eq!(2, pool.stats().idle);
+    }
+
+    #[test(tokio::test)]
+    async fn min_idle_must_not_exceed_size() {
+        let result = Pool::new_with_min_idle(2, 3, AnyObject::new).await;
+        assert!(matches!(result, Err(LazyPoolError::InvalidConfig(_))));
+    }
+
+    #[test(tokio::test)]
+    async fn pool_is_configured_from_config() {
+        let config = PoolConfig {
+            size: 4,
+            min_idle: 2,
+            max_create_retries: 1,
+            get_log_level: Level::Trace,
+            release_log_level: Level::Info,
+            create_log_level: Level::Warn,
+            max_wait: Some(Duration::from_millis(20)),
+        };
+        let pool = Pool::new_with_config(config, SyncFactory::from(AnyObject::new))
+            .await
+            .unwrap();
+        assert_eq!(
+            PoolStats {
+                capacity: 4,
+                idle: 2,
+                in_use: 0
+            },
+            pool.stats()
+        );
+        assert_eq!(2, pool.min_idle);
+        assert_eq!(1, pool.max_create_retries);
+        assert_eq!(
+            LogLevels {
+                get: Level::Trace,
+                release: Level::Info,
+                create: Level::Warn,
+            },
+            pool.log_levels
+        );
+        assert_eq!(Some(Duration::from_millis(20)), pool.max_wait);
+        let objects = pool.try_get_n(4);
+        let result = pool.get_with_max_wait().await;
+        assert!(matches!(result, Err(LazyPoolError::Timeout)));
+        drop(objects);
+        pool.get_with_max_wait().await.unwrap();
+    }
+
+    #[test(tokio::test)]
+    async fn invalid_config_is_rejected() {
+        let config = PoolConfig {
+            size: 1,
+            min_idle: 2,
+            ..PoolConfig::default()
+        };
+        let result = Pool::new_with_config(config, SyncFactory::from(AnyObject::new)).await;
+        assert!(matches!(result, Err(LazyPoolError::InvalidConfig(_))));
+    }
+
+    #[test(tokio::test)]
+    async fn size_limited_pool_grows_under_load_and_shrinks_when_idle() {
+        let pool = Pool::builder()
+            .factory(SyncFactory::from(AnyObject::new))
+            .with_size_limit(1, 3)
+            .build()
+            .await
+            .unwrap();
+        get!(_item_1 = pool => {
+            get!(_item_2 = pool => {
+                get!(_item_3 = pool => {
+                    assert_eq!(3, pool.stats().in_use);
+                    let result = pool.get_timeout(Duration::from_millis(50)).await;
+                    assert!(matches!(result, Err(LazyPoolError::Timeout)));
+                });
+            });
+        });
+        assert_eq!(1, pool.stats().idle);
+        get!(_item_1 = pool => {
+            get!(_item_2 = pool => {
+                assert_eq!(2, pool.stats().in_use);
+            });
+        });
+        assert_eq!(1, pool.stats().idle);
+    }
+
+    #[test(tokio::test)]
+    async fn size_limit_minimum_must_not_exceed_maximum() {
+        let result = Pool::builder()
+            .factory(SyncFactory::from(AnyObject::new))
+            .with_size_limit(3, 1)
+            .build()
+            .await;
+        assert!(matches!(result, Err(LazyPoolError::InvalidConfig(_))));
+    }
+
+    #[test(tokio::test)]
+    async fn monitor_callbacks_are_called_for_lifecycle_events() {
+        let pool = Pool::new(1, Box::new(AnyObject::new)).await.unwrap();
+        let events = Arc::new(SyncMutex::new(Vec::new()));
+        let monitor = pool.monitor();
+        for register in [
+            PoolMonitor::on_checkout,
+            PoolMonitor::on_return,
+            PoolMonitor::on_create,
+            PoolMonitor::on_evict,
+        ] {
+            let local_events = events.clone();
+            register(&monitor, move |data: PoolEventData| {
+                local_events
+                    .lock()
+                    .unwrap()
+                    .push((data.event, data.object_id))
+            });
+        }
+
+        let object_id = get!(item = pool => item.object_id());
+        get!(item = pool => Pooled::tainted(&mut item));
+        let new_object_id = get!(item = pool => item.object_id());
+        assert_ne!(object_id, new_object_id);
+        assert_eq!(
+            vec![
+                (PoolEvent::Create, object_id),
+                (PoolEvent::Checkout, object_id),
+                (PoolEvent::Return, object_id),
+                (PoolEvent::Checkout, object_id),
+                (PoolEvent::Evict, object_id),
+                (PoolEvent::Create, new_object_id),
+                (PoolEvent::Checkout, new_object_id),
+                (PoolEvent::Return, new_object_id),
+            ],
+            *events.lock().unwrap()
+        );
+    }
+
+    #[test(tokio::test)]
+    async fn get_if_ready_resolves_on_first_poll() {
+        let pool = Pool::new(1, Box::new(AnyObject::new)).await.unwrap();
+
+        let mut future = pool.get_if_ready();
+        let Poll::Ready(Ok(object)) = poll!(&mut future) else {
+            panic!("expected an object on first poll");
+        };
+        let object_id = object.object_id();
+
+        let mut future = pool.get_if_ready();
+        assert!(matches!(
+            poll!(&mut future),
+            Poll::Ready(Err(LazyPoolError::Exhausted))
+        ));
+
+        object.release().await.unwrap();
+        let mut future = pool.get_if_ready();
+        let Poll::Ready(Ok(object)) = poll!(&mut future) else {
+            panic!("expected the released object on first poll");
+        };
+        assert_eq!(object_id, object.object_id());
+        object.release().await.unwrap();
+    }
+
+    #[test(tokio::test)]
+    async fn try_get_does_not_wait_for_slow_factories() {
+        let pool = Pool::new_with_factory(1, SlowFactory {}).await.unwrap();
+        assert!(pool.try_get().is_none());
+        assert_eq!(1, pool.stats().capacity);
+        get!(object = pool => {});
+    }
+
+    #[test(tokio::test)]
+    async fn exchange_updates_pooled_object() {
+        let pool = Pool::new(1, Box::new(|| AnyObject::with_context("v1")))
+            .await
+            .unwrap();
+        pool.exchange(|item| item.member = String::from("v2"))
+            .await
+            .unwrap();
+        assert_eq!("v2", get!(item = pool => item.member.clone()));
+    }
+
+    #[test(tokio::test)]
+    async fn exchange_releases_object_when_closure_panics() {
+        let pool = Pool::new(1, Box::new(AnyObject::new)).await.unwrap();
+        let result = AssertUnwindSafe(pool.exchange(|_| panic!("boom")))
+            .catch_unwind()
+            .await;
+        assert!(result.is_err());
+        assert_eq!(0, pool.stats().in_use);
+        let item = pool.get_timeout(Duration::from_millis(100)).await.unwrap();
+        item.release().await.unwrap();
+    }
+
+    #[test(tokio::test)]
+    async fn get_async_with_releases_before_resolving() {
+        let pool = Arc::new(Pool::new(1, Box::new(AnyObject::new)).await.unwrap());
+        let local_pool = pool.clone();
+        let member = pool
+            .get_async_with(|item| {
+                let member = item.member.clone();
+                async move {
+                    assert_eq!(1, local_pool.stats().in_use);
+                    member
+                }
+            })
+            .await
+            .unwrap();
+        assert_eq!(0, pool.stats().in_use);
+        assert_eq!(1, pool.stats().idle);
+        assert_eq!(member, get!(item = pool => item.member.clone()));
+    }
+
+    #[test(tokio::test)]
+    async fn for_each_idle_only_applies_to_idle_objects() {
+        let pool = Pool::new(3, Box::new(AnyObject::new)).await.unwrap();
+        let first = pool.get().await;
+        let second = pool.get().await;
+        let checked_out = pool.get().await;
+        first.release().await.unwrap();
+        second.release().await.unwrap();
+
+        let calls = AtomicU64::new(0);
+        pool.for_each_idle(|item| {
+            calls.fetch_add(1, Ordering::Relaxed);
+            item.member = String::from("updated");
+            async {}
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(2, calls.load(Ordering::Relaxed));
+        assert_eq!(2, pool.stats().idle);
+        assert_ne!("updated", checked_out.member);
+        checked_out.release().await.unwrap();
+        assert_eq!("updated", get!(item = pool => item.member.clone()));
+    }
+
+    #[test(tokio::test)]
+    async fn benchmark_factory_measures_creation_latency() {
+        let pool = Pool::new_with_factory(1, SlowFactory {}).await.unwrap();
+        let benchmark = pool.benchmark_factory(3).await;
+        assert!(benchmark.min >= Duration::from_millis(100));
+        assert!(benchmark.min <= benchmark.p50);
+        assert!(benchmark.p50 <= benchmark.p95);
+        assert!(benchmark.p95 <= benchmark.max);
+        assert!(benchmark.mean >= benchmark.min && benchmark.mean <= benchmark.max);
+        assert_eq!(0, pool.stats().idle);
+        assert_eq!(0, pool.stats().in_use);
+    }
+
+    #[test(tokio::test)]
+    async fn get_latest_hands_out_most_recently_returned_objects() {
+        let pool = Pool::new(3, Box::new(AnyObject::new)).await.unwrap();
+        let a = pool.get().await;
+        let b = pool.get().await;
+        let c = pool.get().await;
+        let ids = [a.object_id(), b.object_id(), c.object_id()];
+        a.release().await.unwrap();
+        b.release().await.unwrap();
+        c.release().await.unwrap();
+
+        let first = pool.get_latest().await;
+        let second = pool.get_latest().await;
+        let third = pool.get_latest().await;
+        assert_eq!(
+            [ids[2], ids[1], ids[0]],
+            [first.object_id(), second.object_id(), third.object_id()]
+        );
+        first.release().await.unwrap();
+        second.release().await.unwrap();
+        third.release().await.unwrap();
+    }
+
+    #[test(tokio::test)]
+    async fn get_latest_keeps_fifo_order_for_remaining_objects() {
+        let pool = Pool::new(3, Box::new(AnyObject::new)).await.unwrap();
+        let a = pool.get().await;
+        let b = pool.get().await;
+        let c = pool.get().await;
+        let ids = [a.object_id(), b.object_id(), c.object_id()];
+        a.release().await.unwrap();
+        b.release().await.unwrap();
+        c.release().await.unwrap();
+
+        let latest = pool.get_latest().await;
+        let oldest = pool.get().await;
+        assert_eq!(ids[2], latest.object_id());
+        assert_eq!(ids[0], oldest.object_id());
+        latest.release().await.unwrap();
+        oldest.release().await.unwrap();
+    }
+
+    #[test(tokio::test)]
+    async fn take_idle_subset_removes_objects_and_their_slots() {
+        let pool = Pool::new(5, Box::new(AnyObject::new)).await.unwrap();
+        pool.warm(5).await.unwrap();
+
+        let taken = pool.take_idle_subset(2).await;
+        assert_eq!(2, taken.len());
+        let stats = pool.stats();
+        assert_eq!(3, stats.capacity);
+        assert_eq!(3, stats.idle);
+
+        let mut objects = Vec::new();
+        for _ in 0..3 {
+            objects.push(pool.get().await);
+        }
+        assert!(pool.try_get().is_none());
+        for object in objects {
+            object.release().await.unwrap();
+        }
+    }
+
+    #[test(tokio::test)]
+    async fn release_errors_include_pool_name_and_object_id() {
+        let pool = Pool::builder()
+            .name("db-pool")
+            .factory(SyncFactory::from(AnyObject::new))
+            .build()
+            .await
+            .unwrap();
+        assert_eq!(Some("db-pool"), pool.name());
+        let object = pool.get().await;
+        let object_id = object.object_id();
+        drop(pool);
+
+        let err = object.release().await.unwrap_err();
+        assert_eq!(
+            format!("[db-pool] failed to release object (id={object_id})"),
+            err.to_string()
+        );
+    }
+
+    #[test(tokio::test)]
+    async fn overflow_objects_are_limited() {
+        let pool = Pool::new(2, Box::new(AnyObject::new)).await.unwrap();
+        let first = pool.get().await;
+        let second = pool.get().await;
+
+        let overflow = pool
+            .get_or_create_overflow_with_limit(1)
+            .now_or_never()
+            .expect("overflow object should be created right away");
+        assert_eq!(2, pool.stats().in_use);
+        let result = select! {
+            _ = pool.get_or_create_overflow_with_limit(1).fuse() => false,
+            _ = Delay::new(Duration::from_millis(50)).fuse() => true,
+        };
+        assert!(result, "fourth checkout should wait");
+
+        overflow.release().await.unwrap();
+        assert_eq!(0, pool.stats().idle);
+        let overflow = pool
+            .get_or_create_overflow_with_limit(1)
+            .now_or_never()
+            .expect("overflow slot should be free again");
+        overflow.release().await.unwrap();
+        first.release().await.unwrap();
+        second.release().await.unwrap();
+    }
+
+    #[test(tokio::test)]
+    async fn pooled_objects_can_be_formatted() {
+        let pool = Pool::new(1, Box::new(|| 42)).await.unwrap();
+        let mut object = pool.get().await;
+        assert_eq!("42", format!("{object}"));
+        assert_eq!("Pooled(tainted=false, value=42)", format!("{object:?}"));
+        object.tainted();
+        assert_eq!("42", format!("{object}"));
+        assert_eq!("Pooled(tainted=true, value=42)", format!("{object:?}"));
+        object.release().await.unwrap();
+    }
+
+    #[test(tokio::test)]
+    async fn take_and_replace_puts_back_the_replacement() {
+        let pool = Pool::new(1, Box::new(|| AnyObject::with_context("original")))
+            .await
+            .unwrap();
+        let previous = pool
+            .take_and_replace(|item| async move {
+                (AnyObject::with_context("replacement"), item.member)
+            })
+            .await
+            .unwrap();
+        assert_eq!("original", previous);
+        assert_eq!("replacement", get!(item = pool => item.member.clone()));
+        assert_eq!(1, pool.stats().idle);
+    }
+
+    #[test(tokio::test)]
+    async fn capacity_transitions_are_detected() {
+        let pool = Pool::new(2, Box::new(AnyObject::new)).await.unwrap();
+        let mut reached = Box::pin(pool.capacity_reached());
+        let mut available = Box::pin(pool.capacity_available());
+        assert_eq!(Poll::Pending, poll!(&mut reached));
+
+        let first = pool.get().await;
+        assert_eq!(Poll::Pending, poll!(&mut reached));
+        let second = pool.get().await;
+        assert_eq!(Poll::Ready(()), poll!(&mut reached));
+        assert_eq!(Poll::Pending, poll!(&mut available));
+
+        first.release().await.unwrap();
+        assert_eq!(Poll::Ready(()), poll!(&mut available));
+        second.release().await.unwrap();
+    }
+
+    #[test(tokio::test)]
+    async fn pool_becomes_degraded_after_repeated_factory_failures() {
+        let pool = Pool::builder()
+            .try_factory(FailingFactory { failures: 3 })
+            .with_failure_threshold(3)
+            .build()
+            .await
+            .unwrap();
+        assert_eq!(HealthStatus::Healthy { idle: 0 }, pool.health_status());
+        for _ in 0..2 {
+            assert!(pool.warm(1).await.is_err());
+            assert!(pool.is_healthy());
+        }
+        assert!(pool.warm(1).await.is_err());
+        assert!(!pool.is_healthy());
+        assert_eq!(
+            HealthStatus::Degraded { recent_failures: 3 },
+            pool.health_status()
+        );
+
+        pool.warm(1).await.unwrap();
+        assert_eq!(HealthStatus::Healthy { idle: 1 }, pool.health_status());
+    }
+
+    #[test(tokio::test)]
+    async fn get_with_retry_replaces_invalid_objects() {
+        let created = Arc::new(AtomicUsize::new(0));
+        let counter = created.clone();
+        let pool = Pool::new(
+            1,
+            Box::new(move || counter.fetch_add(1, Ordering::SeqCst) + 1),
+        )
+        .await
+        .unwrap();
+
+        let object = pool.get_with_retry(|value| *value >= 3, 3).await.unwrap();
+        assert_eq!(3, *object);
+        object.release().await.unwrap();
+
+        let result = pool.get_with_retry(|value| *value > 5, 1).await;
+        assert!(matches!(result, Err(LazyPoolError::ValidationFailed)));
+        assert_eq!(4, created.load(Ordering::SeqCst));
+        assert_eq!(0, pool.stats().in_use);
+    }
+
+    #[test(tokio::test)]
+    async fn paired_pools_share_their_capacity() {
+        let (objects, numbers) = Pool::pair(
+            3,
+            SyncFactory::from(AnyObject::new),
+            SyncFactory::from(|| 42),
+        )
+        .await
+        .unwrap();
+        let first = objects.get().await;
+        let second = objects.get().await;
+        let mut number = numbers.get().await;
+        assert!(objects.try_get().is_none());
+        assert!(numbers.try_get().is_none());
+
+        number.tainted();
+        number.release().await.unwrap();
+        let third = objects
+            .get_timeout(Duration::from_millis(100))
+            .await
+            .unwrap();
+        assert_eq!(3, objects.stats().in_use);
+        assert_eq!(3, numbers.stats().in_use);
+        for object in [first, second, third] {
+            object.release().await.unwrap();
+        }
+    }
+
+    #[test(tokio::test)]
+    async fn pool_can_be_resized() {
+        let pool = Pool::new(2, Box::new(AnyObject::new)).await.unwrap();
+        pool.resize(3);
+        let mut objects = Vec::new();
+        for _ in 0..3 {
+            objects.push(pool.get().await);
+        }
+        assert!(pool.try_get().is_none());
+
+        pool.resize(1);
+        assert_eq!(1, pool.stats().capacity);
+        for object in objects {
+            object.release().await.unwrap();
+        }
+        assert_eq!(1, pool.stats().idle);
+        let object = pool.get().await;
+        assert!(pool.try_get().is_none());
+        object.release().await.unwrap();
+    }
+
+    #[test(tokio::test)]
+    async fn auto_scale_policy_keeps_pool_within_bounds() {
+        let policy = AutoScalePolicy {
+            target_idle_fraction: 0.5,
+            scale_up_threshold: Duration::from_secs(1),
+            scale_down_threshold: Duration::ZERO,
+            min: 1,
+            max: 4,
+        };
+        let pool = Pool::new(1, Box::new(AnyObject::new)).await.unwrap();
+        let mut objects = Vec::new();
+        let mut capacities = Vec::new();
+        for _ in 0..3 {
+            objects.push(pool.get().await);
+            pool.auto_scale_step(&policy, Duration::from_secs(1));
+            capacities.push(pool.stats().capacity);
+        }
+        for object in objects {
+            object.release().await.unwrap();
+            pool.auto_scale_step(&policy, Duration::from_secs(1));
+            capacities.push(pool.stats().capacity);
+        }
+        assert_eq!(vec![2, 4, 4, 4, 2, 1], capacities);
+        assert_eq!(1, pool.stats().idle);
+    }
+
+    #[test(tokio::test)]
+    async fn auto_scale_policy_follows_wait_and_idle_times() {
+        let policy = AutoScalePolicy::new(
+            0.0,
+            Duration::from_millis(20),
+            Duration::from_millis(50),
+            1,
+            3,
+        )
+        .unwrap();
+        let pool = Arc::new(Pool::new(1, Box::new(AnyObject::new)).await.unwrap());
+        let mut capacities = Vec::new();
+        for _ in 0..3 {
+            let held = pool.try_get_n(pool.stats().capacity);
+            let waiting_pool = pool.clone();
+            let waiting = tokio::spawn(async move { waiting_pool.get().await.release().await });
+            Delay::new(Duration::from_millis(40)).await;
+            drop(held);
+            waiting.await.unwrap().unwrap();
+            pool.auto_scale_step(&policy, Duration::from_millis(20));
+            capacities.push(pool.stats().capacity);
+        }
+        pool.auto_scale_step(&policy, Duration::from_millis(1));
+        capacities.push(pool.stats().capacity);
+        Delay::new(Duration::from_millis(50)).await;
+        pool.auto_scale_step(&policy, Duration::from_millis(1));
+        capacities.push(pool.stats().capacity);
+        assert_eq!(vec![2, 3, 3, 3, 1], capacities);
+    }
+
+    #[test]
+    fn auto_scale_policy_rejects_invalid_bounds() {
+        let result = AutoScalePolicy::new(0.2, Duration::ZERO, Duration::ZERO, 3, 1);
+        assert!(matches!(result, Err(LazyPoolError::InvalidConfig(_))));
+        let result = AutoScalePolicy::new(1.0, Duration::ZERO, Duration::ZERO, 1, 3);
+        assert!(matches!(result, Err(LazyPoolError::InvalidConfig(_))));
+        let policy = AutoScalePolicy {
+            target_idle_fraction: 0.2,
+            scale_up_threshold: Duration::ZERO,
+            scale_down_threshold: Duration::ZERO,
+            min: 3,
+            max: 1,
+        };
+        let stats = PoolStats {
+            capacity: 2,
+            idle: 0,
+            in_use: 2,
+        };
+        assert_eq!(Some(1), policy.target_size(stats));
+    }
+
+    #[test(tokio::test)]
+    async fn budgeted_get_accounts_for_wait_time() {
+        let pool = Pool::new_with_factory(1, SlowFactory {}).await.unwrap();
+        let started = Instant::now();
+        let object = pool.get_within_budget(1000).await.unwrap();
+        let waited = started.elapsed().as_millis() as u64;
+        assert!(waited >= 100);
+        assert!(object.remaining_budget_ms() <= 1000 - waited);
+        assert!(!object.member.is_empty());
+        object.release().await.unwrap();
+
+        let object = pool.get().await;
+        let result = pool.get_within_budget(10).await;
+        assert!(matches!(result, Err(LazyPoolError::Timeout)));
+        object.release().await.unwrap();
+    }
+
+    #[test(tokio::test)]
+    async fn lazy_get_only_checks_out_when_polled() {
+        let pool = Pool::new(1, Box::new(AnyObject::new)).await.unwrap();
+        let first = pool.get_lazily();
+        let second = pool.get_lazily();
+        assert_eq!(0, pool.stats().in_use);
+        assert_eq!(0, pool.stats().idle);
+
+        let object = first.await;
+        assert_eq!(1, pool.stats().in_use);
+        object.release().await.unwrap();
+        let object = second.await;
+        object.release().await.unwrap();
+    }
+
+    #[test(tokio::test)]
+    async fn shared_object_is_released_when_last_clone_is_dropped() {
+        let pool = Pool::new(1, Box::new(AnyObject::new)).await.unwrap();
+        let first = pool.get_shared().await;
+        let second = first.clone();
+        assert_eq!(first.member, second.member);
+
+        drop(first);
+        assert_eq!(1, pool.stats().in_use);
+        assert!(pool.try_get().is_none());
+        drop(second);
+        assert_eq!(0, pool.stats().in_use);
+        assert_eq!(1, pool.stats().idle);
+    }
+
+    #[test(tokio::test)]
+    async fn try_warm_only_uses_free_slots() {
+        let pool = Pool::new(5, Box::new(AnyObject::new)).await.unwrap();
+        let first = pool.get().await;
+        let second = pool.get().await;
+        assert_eq!(3, pool.try_warm(10).await);
+        assert_eq!(3, pool.stats().idle);
+        first.release().await.unwrap();
+        second.release().await.unwrap();
+        assert_eq!(0, pool.try_warm(5).await);
+        assert_eq!(5, pool.stats().idle);
+    }
+
+    #[test(tokio::test)]
+    async fn shut_down_pool_does_not_hand_out_objects() {
+        let pool = Pool::new_eager(3, Box::new(AnyObject::new)).await.unwrap();
+        let checked_out = pool.get().await;
+
+        assert_eq!(2, pool.drain_and_shutdown().await.len());
+        assert!(pool.try_get().is_none());
+        let result = pool.get_timeout(Duration::from_millis(10)).await;
+        assert!(matches!(result, Err(LazyPoolError::Closed)));
+        let mut checkout = Box::pin(pool.get());
+        assert!(poll!(&mut checkout).is_pending());
+        assert!(poll!(&mut checkout).is_pending());
+
+        checked_out.release().await.unwrap();
+        assert_eq!(0, pool.stats().idle);
+        assert!(pool.drain_and_shutdown().await.is_empty());
+    }
+
+    #[test(tokio::test)]
+    async fn shutdown_wakes_up_waiting_checkouts() {
+        let pool = Pool::new(1, Box::new(AnyObject::new)).await.unwrap();
+        let checked_out = pool.get().await;
+        let mut waiting = Box::pin(pool.get());
+        assert!(poll!(&mut waiting).is_pending());
+        let mut draining = Box::pin(pool.drain_and_shutdown());
+        assert!(poll!(&mut draining).is_pending());
+        // Woken up by the shutdown, lets go of the idle objects and keeps
+        // waiting.
+        assert!(poll!(&mut waiting).is_pending());
+        assert!(draining.await.is_empty());
+        checked_out.release().await.unwrap();
+        assert!(poll!(&mut waiting).is_pending());
+    }
+
+    #[test(tokio::test)]
+    async fn await_all_returned_resolves_when_last_object_is_released() {
+        let pool = Pool::new(2, Box::new(AnyObject::new)).await.unwrap();
+        let first = pool.get().await;
+        let second = pool.get().await;
+        pool.drain_and_shutdown().await;
+
+        let mut all_returned = Box::pin(pool.await_all_returned());
+        assert_eq!(Poll::Pending, poll!(&mut all_returned));
+        first.release().await.unwrap();
+        assert_eq!(Poll::Pending, poll!(&mut all_returned));
+        second.release().await.unwrap();
+        assert_eq!(Poll::Ready(()), poll!(&mut all_returned));
+    }
+
+    #[test(tokio::test)]
+    async fn priority_checkouts_are_served_in_priority_order() {
+        let pool = Pool::new(1, Box::new(AnyObject::new)).await.unwrap();
+        let object = pool.get().await;
+        let mut low = Box::pin(pool.get_with_priority(5));
+        let mut high = Box::pin(pool.get_with_priority(1));
+        let mut medium = Box::pin(pool.get_with_priority(3));
+        assert!(poll!(&mut low).is_pending());
+        assert!(poll!(&mut high).is_pending());
+        assert!(poll!(&mut medium).is_pending());
+
+        object.release().await.unwrap();
+        assert!(poll!(&mut low).is_pending());
+        assert!(poll!(&mut medium).is_pending());
+        let Poll::Ready(object) = poll!(&mut high) else {
+            panic!("highest priority checkout should be served first");
+        };
+        object.release().await.unwrap();
+        assert!(poll!(&mut low).is_pending());
+        let Poll::Ready(object) = poll!(&mut medium) else {
+            panic!("medium priority checkout should be served second");
+        };
+        object.release().await.unwrap();
+        low.await.release().await.unwrap();
+    }
+
+    #[test(tokio::test)]
+    async fn equal_priority_checkouts_are_served_in_order() {
+        let pool = Pool::new(1, Box::new(AnyObject::new)).await.unwrap();
+        let object = pool.get().await;
+        let mut first = Box::pin(pool.get_with_priority(1));
+        let mut second = Box::pin(pool.get_with_priority(1));
+        assert!(poll!(&mut first).is_pending());
+        assert!(poll!(&mut second).is_pending());
+
+        object.release().await.unwrap();
+        assert!(poll!(&mut second).is_pending());
+        let Poll::Ready(object) = poll!(&mut first) else {
+            panic!("first checkout should be served first");
+        };
+        object.release().await.unwrap();
+        second.await.release().await.unwrap();
+    }
+
+    #[test(tokio::test)]
+    async fn cancelled_priority_checkouts_are_pruned() {
+        let pool = Pool::new(1, Box::new(AnyObject::new)).await.unwrap();
+        let object = pool.get().await;
+        for _ in 0..100 {
+            let mut cancelled = Box::pin(pool.get_with_priority(1));
+            assert!(poll!(&mut cancelled).is_pending());
+        }
+        let mut waiting = Box::pin(pool.get_with_priority(1));
+        assert!(poll!(&mut waiting).is_pending());
+        assert_eq!(1, pool.waiters.len());
+
+        object.release().await.unwrap();
+        waiting.await.release().await.unwrap();
+        assert_eq!(1, pool.stats().idle);
+    }
+
+    #[test(tokio::test)]
+    async fn windowed_statistics_only_reflect_recent_activity() {
+        let pool = Pool::new_with_try_factory(1, FailingFactory { failures: 1 })
+            .await
+            .unwrap();
+        for _ in 0..4 {
+            get!(item = pool => item.member.clone());
+        }
+        let burst = pool.statistics_window(Duration::from_secs(2));
+        assert_eq!(2.0, burst.checkouts_per_second);
+        assert_eq!(0.5, burst.creates_per_second);
+        assert_eq!(0.5, burst.error_rate);
+        assert!(burst.mean_wait_ms >= 0.0);
+
+        Delay::new(Duration::from_millis(50)).await;
+        let quiet = pool.statistics_window(Duration::from_millis(25));
+        assert_eq!(WindowedStats::default(), quiet);
+    }
+
+    #[test(tokio::test)]
+    async fn checkouts_are_rate_limited() {
+        let pool = Pool::builder()
+            .factory(SyncFactory::from(AnyObject::new))
+            .with_rate_limit(1000.0)
+            .build()
+            .await
+            .unwrap();
+        let started = Instant::now();
+        for _ in 0..100 {
+            get!(item = pool => item.member.clone());
+        }
+        // The first checkout is let through right away.
+        assert!(started.elapsed() >= Duration::from_millis(99));
+        assert!(pool.try_get().is_none());
+    }
+
+    #[cfg(feature = "tracing")]
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn get_traced_records_span_with_pool_fields() {
+        let pool = Pool::builder()
+            .name("db")
+            .factory(SyncFactory::from(AnyObject::new))
+            .build()
+            .await
+            .unwrap();
+        let member = get_traced!(item = pool, span_name = "my_operation" => {
+            tracing::info!("inside block");
+            item.member.clone()
+        });
+        assert!(!member.is_empty());
+        assert!(logs_contain(
+            "my_operation{pool.name=\"db\" pool.idle=0 pool.wait_ms=0}"
+        ));
+        assert!(logs_contain("inside block"));
+    }
+
+    #[cfg(feature = "tracing")]
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn factory_events_are_recorded_in_callers_span() {
+        let pool = Pool::new(1, || {
+            tracing::info!("creating object");
+            AnyObject::new()
+        })
+        .await
+        .unwrap();
+        let checkout =
+            tracing::info_span!("request", id = 7).in_scope(|| pool.get_with_tracing_propagation());
+        let object = checkout.await;
+        object.release().await.unwrap();
+        logs_assert(|lines| {
+            let in_span =
+                |line: &&str| line.contains("request{id=7}") && line.contains("creating object");
+            if lines.iter().any(in_span) {
+                Ok(())
+            } else {
+                Err(String::from("factory event is missing the caller's span"))
+            }
+        });
+    }
+
+    #[test(tokio::test)]
+    async fn get_traced_falls_back_to_get() {
+        let pool = Pool::new(1, Box::new(AnyObject::new)).await.unwrap();
+        let member = get_traced!(item = pool, span_name = "my_operation" => item.member.clone());
+        assert_eq!(member, get!(item = pool => item.member.clone()));
+    }
+
+    #[test(tokio::test)]
+    async fn fallback_is_used_when_pool_is_exhausted() {
+        let pool = Pool::new(1, Box::new(|| AnyObject::with_context("pooled")))
+            .await
+            .unwrap();
+        let timeout = Duration::from_millis(10);
+        let member = |item: &mut AnyObject| item.member.clone();
+
+        let value = pool
+            .get_with_fallback(timeout, member, String::from("fallback"))
+            .await;
+        assert_eq!("pooled", value);
+
+        let object = pool.get().await;
+        let value = pool
+            .get_with_fallback(timeout, member, String::from("fallback"))
+            .await;
+        assert_eq!("fallback", value);
+        object.release().await.unwrap();
+    }
+
+    #[cfg(feature = "tcp")]
+    #[test(tokio::test)]
+    async fn tcp_connections_are_pooled_and_checked() {
+        use crate::integrations::connection::{TcpConnectionFactory, TcpHealthCheck};
+        use tokio::net::TcpListener;
+
+        assert!(TcpConnectionFactory::from_url("http://localhost:80").is_err());
+        assert!(TcpConnectionFactory::from_url("tcp://localhost").is_err());
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("tcp://{}", listener.local_addr().unwrap());
+        let pool = Pool::builder()
+            .size(1)
+            .try_factory(TcpConnectionFactory::from_url(&url).unwrap())
+            .with_return_validator(TcpHealthCheck::is_connected)
+            .build()
+            .await
+            .unwrap();
+        let stream = pool.get().await;
+        let (server_side, _) = listener.accept().await.unwrap();
+        assert!(TcpHealthCheck::is_connected(&stream));
+
+        drop(server_side);
+        Delay::new(Duration::from_millis(50)).await;
+        assert!(!TcpHealthCheck::is_connected(&stream));
+        stream.release().await.unwrap();
+        assert_eq!(0, pool.stats().idle);
+    }
+
+    #[test(tokio::test)]
+    async fn get_or_wait_for_idle_keeps_headroom() {
+        let pool = Pool::new(4, Box::new(AnyObject::new)).await.unwrap();
+        let first = pool.get().await;
+        let second = pool.get().await;
+
+        let mut waiting = Box::pin(pool.get_or_wait_for_idle(2));
+        assert!(poll!(&mut waiting).is_pending());
+        first.release().await.unwrap();
+        let object = waiting.await;
+        assert_eq!(2, pool.stats().in_use);
+        object.release().await.unwrap();
+        second.release().await.unwrap();
+    }
+
+    #[test(tokio::test)]
+    async fn pooled_streams_can_be_read_and_written() {
+        use std::io::{Cursor, Read, Write};
+        use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+        let pool = Pool::new(1, Box::new(|| Cursor::new(Vec::<u8>::new())))
+            .await
+            .unwrap();
+        let mut stream = pool.get().await;
+        tokio::io::copy(&mut &b"hello "[..], &mut stream)
+            .await
+            .unwrap();
+        stream.write_all(b"world").unwrap();
+        stream.set_position(0);
+        let mut sync_read = [0; 5];
+        Read::read_exact(&mut stream, &mut sync_read).unwrap();
+        assert_eq!(b"hello", &sync_read);
+        let mut async_read = String::new();
+        AsyncReadExt::read_to_string(&mut stream, &mut async_read)
+            .await
+            .unwrap();
+        assert_eq!(" world", async_read);
+        stream.rewind().await.unwrap();
+        stream.release().await.unwrap();
+    }
+
+    #[test(tokio::test)]
+    async fn get_and_return_counts_track_round_trips() {
+        let pool = Pool::new(2, Box::new(|| 1)).await.unwrap();
+        assert_eq!((pool.get_count(), pool.return_count()), (0, 0));
+        let first = pool.get().await;
+        let mut second = pool.get().await;
+        assert_eq!((pool.get_count(), pool.return_count()), (2, 0));
+        first.release().await.unwrap();
+        second.tainted();
+        second.release().await.unwrap();
+        assert_eq!((pool.get_count(), pool.return_count()), (2, 2));
+        let object = pool.get().await;
+        assert_eq!(pool.get_count() - pool.return_count(), 1);
+        assert_eq!(pool.stats().in_use, 1);
+        object.release().await.unwrap();
+        assert_eq!(pool.get_count(), pool.return_count());
+    }
+
+    #[test(tokio::test)]
+    async fn get_if_below_threshold_ignores_spare_capacity() {
+        let pool = Pool::new(5, Box::new(|| 1)).await.unwrap();
+        let first = pool.get_if_below_threshold(2).await.unwrap();
+        let second = pool.get_if_below_threshold(2).await.unwrap();
+        assert!(pool.get_if_below_threshold(2).await.is_none());
+        assert_eq!(pool.stats().capacity, 5);
+        let third = pool.get_if_below_threshold(3).await.unwrap();
+        first.release().await.unwrap();
+        let fourth = pool.get_if_below_threshold(3).await.unwrap();
+        for object in [second, third, fourth] {
+            object.release().await.unwrap();
+        }
+    }
+
+    #[test(tokio::test)]
+    async fn health_report_reflects_pool_state() {
+        let pool = Pool::builder()
+            .name("reported")
+            .size(3)
+            .factory(SyncFactory::from(|| 1))
+            .build()
+            .await
+            .unwrap();
+        let report = pool.health_report().await;
+        assert_eq!(report.oldest_idle, None);
+        assert_eq!(report.total_creates, 0);
+
+        let first = pool.get().await;
+        let mut second = pool.get().await;
+        let third = pool.get().await;
+        first.release().await.unwrap();
+        second.tainted();
+        second.release().await.unwrap();
+        thread::sleep(Duration::from_millis(20));
+
+        let report = pool.health_report().await;
+        assert_eq!(report.name.as_deref(), Some("reported"));
+        assert_eq!((report.capacity, report.idle, report.in_use), (3, 1, 1));
+        assert_eq!((report.total_creates, report.total_taints), (3, 1));
+        assert_eq!(report.recent_failures, 0);
+        assert_eq!(report.status, HealthStatus::Healthy { idle: 1 });
+        assert!(report.oldest_idle.unwrap() >= Duration::from_millis(20));
+        let table = report.to_string();
+        assert!(table.contains("reported"));
+        assert!(table.contains("total taints     1"));
+        assert_eq!(pool.stats().idle, 1);
+        third.release().await.unwrap();
+        assert_eq!(pool.try_get().map(|object| *object), Some(1));
+    }
+
+    #[test(tokio::test)]
+    async fn health_report_does_not_wait_for_waiting_checkouts() {
+        let pool = Pool::new(1, Box::new(AnyObject::new)).await.unwrap();
+        let object = pool.get().await;
+        let mut waiting = Box::pin(pool.get());
+        assert!(poll!(&mut waiting).is_pending());
+
+        let report = pool.health_report().now_or_never().unwrap();
+        assert_eq!((report.idle, report.in_use), (0, 1));
+        assert_eq!(report.oldest_idle, None);
+        object.release().await.unwrap();
+        waiting.await.release().await.unwrap();
+    }
+
+    #[cfg(feature = "serde")]
+    #[test(tokio::test)]
+    async fn health_report_serializes_to_json() {
+        let pool = Pool::new(2, Box::new(|| 1)).await.unwrap();
+        let json = pool.health_report().await.json();
+        assert!(json.contains("\"capacity\":2"));
+        assert!(json.contains("\"status\":{\"Healthy\":{\"idle\":0}}"));
+    }
+
+    #[test(tokio::test)]
+    async fn get_via_semaphore_reuses_objects_and_respects_size() {
+        let pool = Pool::new(2, Box::new(AnyObject::new)).await.unwrap();
+        let mut first = pool.get_via_semaphore().await.unwrap();
+        first.member = String::from("reused");
+        let second = pool.get_via_semaphore().await.unwrap();
+        let mut third = Box::pin(pool.get_via_semaphore());
+        assert!(poll!(&mut third).is_pending());
+        first.release();
+        let third = third.await.unwrap();
+        assert_eq!("reused", third.member);
+        assert_eq!(0, pool.stats().in_use);
+        drop(second);
+        drop(third);
+    }
+
+    #[test(tokio::test)]
+    async fn get_via_semaphore_does_not_lose_permits_when_cancelled() {
+        let pool = Pool::new_with_factory(2, SlowFactory {}).await.unwrap();
+        for _ in 0..3 {
+            let mut creating = Box::pin(pool.get_via_semaphore());
+            assert!(poll!(&mut creating).is_pending());
+        }
+        let held = pool.get_via_semaphore().await.unwrap();
+        let attempts = (0..8u64).map(|attempt| {
+            let pool = &pool;
+            async move {
+                let checkout = pool.get_via_semaphore().fuse();
+                let timeout = Delay::new(Duration::from_millis(attempt * 40)).fuse();
+                futures::pin_mut!(checkout, timeout);
+                select! {
+                    object = checkout => {
+                        Delay::new(Duration::from_millis(20)).await;
+                        drop(object);
+                    },
+                    _ = timeout => {},
+                }
+            }
+        });
+        futures::future::join_all(attempts).await;
+        drop(held);
+        assert_eq!(2, pool.semaphore_slots.permits.available_permits());
+        assert!(pool.semaphore_slots.idle.lock().unwrap().len() <= 2);
+        let first = pool.get_via_semaphore().await.unwrap();
+        let second = pool.get_via_semaphore().await.unwrap();
+        assert!(pool.get_via_semaphore().now_or_never().is_none());
+        drop((first, second));
+    }
+
+    #[test(tokio::test)]
+    async fn resize_preserving_checked_out_converges_as_objects_are_released() {
+        let pool = Pool::new(4, Box::new(AnyObject::new)).await.unwrap();
+        let first = pool.get().await;
+        let second = pool.get().await;
+        let third = pool.get().await;
+
+        let mut resized = Box::pin(pool.resize_preserving_checked_out(1));
+        assert_eq!(Poll::Pending, poll!(&mut resized));
+        assert_eq!(1, pool.stats().capacity);
+        assert!(pool.try_get().is_none());
+        first.release().await.unwrap();
+        assert_eq!(Poll::Pending, poll!(&mut resized));
+        second.release().await.unwrap();
+        assert_eq!(Poll::Ready(()), poll!(&mut resized));
+        assert_eq!(0, pool.stats().idle);
+
+        third.release().await.unwrap();
+        assert_eq!(1, pool.stats().idle);
+        let object = pool.get().await;
+        assert!(pool.try_get().is_none());
+        object.release().await.unwrap();
+
+        pool.resize_preserving_checked_out(3).await;
+        assert_eq!(3, pool.stats().capacity);
+    }
+
+    #[test(tokio::test)]
+    async fn get_deadline_aware_only_times_out_with_a_deadline() {
+        let pool = Pool::new(1, Box::new(AnyObject::new)).await.unwrap();
+        let checked_out = pool.get_deadline_aware(None).await.unwrap();
+        let deadline = Instant::now() + Duration::from_millis(50);
+        let result = pool.get_deadline_aware(Some(deadline)).await;
+        assert!(matches!(result, Err(LazyPoolError::Timeout)));
+        assert!(Instant::now() >= deadline);
+
+        let mut waiting = Box::pin(pool.get_deadline_aware(None));
+        assert!(poll!(&mut waiting).is_pending());
+        checked_out.release().await.unwrap();
+        waiting.await.unwrap().release().await.unwrap();
+    }
+
+    #[test(tokio::test)]
+    async fn get_or_spin_falls_back_to_waiting() {
+        let pool = Pool::new(1, Box::new(AnyObject::new)).await.unwrap();
+        pool.get_or_spin(0).await.release().await.unwrap();
+        let checked_out = pool.get_or_spin(10).await;
+        let id = checked_out.object_id();
+
+        let mut spinning = Box::pin(pool.get_or_spin(100));
+        assert!(poll!(&mut spinning).is_pending());
+        checked_out.release().await.unwrap();
+        let object = spinning.await;
+        assert_eq!(id, object.object_id());
+        object.release().await.unwrap();
+    }
+
+    #[test(tokio::test)]
+    async fn into_async_iter_yields_every_object_once() {
+        let pool = Pool::new(3, Box::new(AnyObject::new)).await.unwrap();
+        let idle = pool.get().await;
+        let checked_out = pool.get().await;
+        idle.release().await.unwrap();
+
+        let objects = pool.into_async_iter();
+        futures::pin_mut!(objects);
+        let mut members = Vec::new();
+        for _ in 0..2 {
+            members.push(objects.next().await.unwrap().member);
+        }
+        assert!(poll!(objects.next()).is_pending());
+        checked_out.release().await.unwrap();
+        members.push(objects.next().await.unwrap().member);
+        assert!(objects.next().await.is_none());
+        assert_eq!(3, HashSet::<String>::from_iter(members).len());
+    }
+
+    #[test]
+    fn pool_into_iter_yields_size_objects() {
+        let pool = block_on(Pool::new(4, Box::new(AnyObject::new))).unwrap();
+        block_on(pool.warm(2)).unwrap();
+        let mut objects = pool.into_iter();
+        assert_eq!((4, Some(4)), objects.size_hint());
+        let members: HashSet<String> = objects.by_ref().map(|object| object.member).collect();
+        assert_eq!(4, members.len());
+        assert!(objects.next().is_none());
+    }
+
+    #[test(tokio::test)]
+    async fn object_ids_lists_idle_objects() {
+        let pool = Pool::new_eager(3, Box::new(AnyObject::new)).await.unwrap();
+        let ids = pool.object_ids().await;
+        assert_eq!(3, HashSet::<Uuid>::from_iter(ids.clone()).len());
+
+        let object = pool.get().await;
+        assert_eq!(ids[0], object.object_id());
+        assert_eq!(&ids[1..], &pool.object_ids().await[..]);
+        object.release().await.unwrap();
+        assert_eq!(3, pool.object_ids().await.len());
+        assert_eq!(3, pool.stats().idle);
+    }
+
+    #[test(tokio::test)]
+    async fn object_ids_does_not_wait_for_waiting_checkouts() {
+        let pool = Pool::new(1, Box::new(AnyObject::new)).await.unwrap();
+        let object = pool.get().await;
+        let mut waiting = Box::pin(pool.get());
+        assert!(poll!(&mut waiting).is_pending());
+
+        assert!(pool.object_ids().now_or_never().unwrap().is_empty());
+        object.release().await.unwrap();
+        waiting.await.release().await.unwrap();
+        assert_eq!(1, pool.object_ids().await.len());
+    }
+
+    #[test(tokio::test)]
+    async fn dropped_objects_are_released() {
+        let pool = Pool::new(1, Box::new(AnyObject::new)).await.unwrap();
+        let object = pool.get().await;
+        let member = object.member.clone();
+        drop(object);
+        assert_eq!(0, pool.stats().in_use);
+        assert_eq!(1, pool.stats().idle);
+        let mut object = pool.get().await;
+        assert_eq!(member, object.member);
+        object.tainted();
+        drop(object);
+        assert_eq!((0, 0), (pool.stats().in_use, pool.stats().idle));
+        assert_eq!(pool.get_count(), pool.return_count());
+    }
+
+    #[test(tokio::test)]
+    async fn pinned_self_referential_objects_can_be_polled_and_released() {
+        type Generator = Pin<Box<dyn Future<Output = usize> + Send>>;
+        let pool = Pool::new(
+            1,
+            Box::new(|| -> Generator {
+                Box::pin(async {
+                    let data = [1, 2, 3];
+                    let borrowed = &data[..];
+                    Delay::new(Duration::from_millis(1)).await;
+                    borrowed.iter().sum()
+                })
+            }),
         )
+        .await
+        .unwrap();
+
+        let mut generator = pool.get_pin().await;
+        let id = generator.object_id();
+        assert!(poll!(generator.as_mut()).is_pending());
+        assert_eq!(6, generator.as_mut().await);
+        drop(generator);
+        assert_eq!(1, pool.stats().idle);
+
+        let mut generator = pool.get_pin().await;
+        assert_eq!(id, generator.object_id());
+        generator.tainted();
+        Pin::into_inner(generator).release().await.unwrap();
+        assert_eq!(0, pool.stats().idle);
+    }
+
+    /**
+    Installs a global tracer provider exporting to memory, once for all
+    tests. Spans are exported in the background rather than through the
+    simple exporter, which panics when a span ends within `block_on`.
+    */
+    #[cfg(feature = "opentelemetry")]
+    fn exported_spans() -> &'static (
+        opentelemetry_sdk::trace::SdkTracerProvider,
+        opentelemetry_sdk::trace::InMemorySpanExporter,
+    ) {
+        use opentelemetry::global;
+        use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider};
+        use std::sync::OnceLock;
+
+        static SPANS: OnceLock<(SdkTracerProvider, InMemorySpanExporter)> = OnceLock::new();
+        SPANS.get_or_init(|| {
+            let spans = InMemorySpanExporter::default();
+            let provider = SdkTracerProvider::builder()
+                .with_batch_exporter(spans.clone())
+                .build();
+            global::set_tracer_provider(provider.clone());
+            (provider, spans)
+        })
+    }
+
+    #[cfg(feature = "opentelemetry")]
+    #[test(tokio::test)]
+    async fn telemetry_emits_spans_and_metrics() {
+        use opentelemetry::global;
+        use opentelemetry_sdk::metrics::{
+            data::Sum, InMemoryMetricExporter, PeriodicReader, SdkMeterProvider,
+        };
+
+        let (tracer_provider, spans) = exported_spans();
+        let metrics = InMemoryMetricExporter::default();
+        let meter_provider = SdkMeterProvider::builder()
+            .with_reader(PeriodicReader::builder(metrics.clone()).build())
+            .build();
+        global::set_meter_provider(meter_provider.clone());
+
+        let pool = Pool::builder()
+            .with_telemetry_prefix("telemetry_test")
+            .size(2)
+            .factory(SyncFactory::from(AnyObject::new))
+            .build()
+            .await
+            .unwrap();
+        pool.get().await.release().await.unwrap();
+        pool.get().await.release().await.unwrap();
+        meter_provider.force_flush().unwrap();
+        tracer_provider.force_flush().unwrap();
+
+        let span_names: Vec<_> = spans
+            .get_finished_spans()
+            .unwrap()
+            .into_iter()
+            .map(|span| span.name)
+            .filter(|name| name.starts_with("telemetry_test."))
+            .collect();
+        assert_eq!(
+            2,
+            span_names
+                .iter()
+                .filter(|name| *name == "telemetry_test.pool.get")
+                .count()
+        );
+        assert_eq!(
+            1,
+            span_names
+                .iter()
+                .filter(|name| *name == "telemetry_test.pool.create")
+                .count()
+        );
+
+        let exported = metrics.get_finished_metrics().unwrap();
+        let emitted: Vec<_> = exported
+            .iter()
+            .flat_map(|resource| &resource.scope_metrics)
+            .flat_map(|scope| &scope.metrics)
+            .filter(|metric| metric.name.starts_with("telemetry_test."))
+            .collect();
+        let names: HashSet<_> = emitted.iter().map(|metric| metric.name.as_ref()).collect();
+        assert_eq!(
+            HashSet::from_iter([
+                "telemetry_test.pool.idle",
+                "telemetry_test.pool.checkouts",
+                "telemetry_test.pool.wait_duration",
+            ]),
+            names
+        );
+        let checkouts = emitted
+            .iter()
+            .find(|metric| metric.name == "telemetry_test.pool.checkouts")
+            .and_then(|metric| metric.data.as_any().downcast_ref::<Sum<u64>>())
+            .unwrap();
+        assert_eq!(2, checkouts.data_points[0].value);
+    }
+
+    #[test(tokio::test)]
+    async fn try_get_n_returns_as_many_objects_as_are_available() {
+        let pool = Pool::new_eager(3, Box::new(AnyObject::new)).await.unwrap();
+        let objects = pool.try_get_n(5);
+        assert_eq!(3, objects.len());
+        assert!(pool.try_get_n(1).is_empty());
+        for object in objects {
+            object.release().await.unwrap();
+        }
+        assert_eq!(3, pool.stats().idle);
+        assert_eq!(2, pool.try_get_n(2).len());
+        assert_eq!(3, pool.stats().idle);
+    }
+
+    #[test(tokio::test)]
+    async fn pre_create_hook_runs_once_per_creation() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let pool = Pool::builder()
+            .size(2)
+            .factory(SyncFactory::from(AnyObject::new))
+            .with_pre_create_hook(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+            })
+            .build()
+            .await
+            .unwrap();
+        pool.get().await.release().await.unwrap();
+        pool.get().await.release().await.unwrap();
+        assert_eq!(1, calls.load(Ordering::SeqCst));
+        let first = pool.get().await;
+        let second = pool.get().await;
+        assert_eq!(2, calls.load(Ordering::SeqCst));
+        drop((first, second));
+    }
+
+    #[test(tokio::test)]
+    async fn async_pre_create_hook_runs_before_the_factory() {
+        let events = Arc::new(SyncMutex::new(Vec::new()));
+        let (hook_events, factory_events) = (events.clone(), events.clone());
+        let pool = Pool::builder()
+            .size(1)
+            .factory(SyncFactory::from(move || {
+                factory_events.lock().unwrap().push("create");
+                AnyObject::new()
+            }))
+            .with_async_pre_create_hook(move || {
+                let events = hook_events.clone();
+                async move {
+                    Delay::new(Duration::from_millis(10)).await;
+                    events.lock().unwrap().push("hook");
+                }
+            })
+            .build()
+            .await
+            .unwrap();
+        pool.get().await.release().await.unwrap();
+        pool.get().await.release().await.unwrap();
+        assert_eq!(vec!["hook", "create"], *events.lock().unwrap());
+    }
+
+    #[test(tokio::test)]
+    async fn post_return_hook_skips_tainted_and_invalid_objects() {
+        let returned = Arc::new(SyncMutex::new(Vec::new()));
+        let hook_returned = returned.clone();
+        let pool = Pool::builder()
+            .size(3)
+            .factory(SyncFactory::from(AnyObject::new))
+            .with_return_validator(|item: &AnyObject| item.member != "invalid")
+            .with_post_return_hook(move |item: &AnyObject| {
+                hook_returned.lock().unwrap().push(item.member.clone());
+            })
+            .build()
+            .await
+            .unwrap();
+        let valid = pool.get().await;
+        let member = valid.member.clone();
+        let mut tainted = pool.get().await;
+        let mut invalid = pool.get().await;
+        tainted.tainted();
+        invalid.member = String::from("invalid");
+        for object in [valid, tainted, invalid] {
+            object.release().await.unwrap();
+        }
+        assert_eq!(vec![member], *returned.lock().unwrap());
+        assert_eq!(1, pool.stats().idle);
+    }
+
+    #[test(tokio::test)]
+    async fn post_checkout_hook_runs_for_new_and_reused_objects() {
+        let checked_out = Arc::new(SyncMutex::new(Vec::new()));
+        let hook_checked_out = checked_out.clone();
+        let pool = Pool::builder()
+            .size(2)
+            .factory(SyncFactory::from(AnyObject::new))
+            .with_post_checkout_hook(move |item: &AnyObject| {
+                hook_checked_out.lock().unwrap().push(item.member.clone());
+            })
+            .build()
+            .await
+            .unwrap();
+        let created = pool.get().await;
+        let member = created.member.clone();
+        created.release().await.unwrap();
+        let reused = pool.try_get().unwrap();
+        let priority = pool.get_with_priority(1).await;
+        assert_eq!(member, reused.member);
+        let expected = vec![member.clone(), member, priority.member.clone()];
+        assert_eq!(expected, *checked_out.lock().unwrap());
+        drop((reused, priority));
+    }
+
+    #[test(tokio::test)]
+    async fn async_post_checkout_hook_releases_cancelled_checkouts() {
+        let pool = Pool::builder()
+            .size(1)
+            .factory(SyncFactory::from(AnyObject::new))
+            .with_async_post_checkout_hook(|item: &AnyObject| {
+                async move {
+                    Delay::new(Duration::from_millis(10)).await;
+                    assert!(!item.member.is_empty());
+                }
+                .boxed()
+            })
+            .build()
+            .await
+            .unwrap();
+        let object = pool.get().await;
+        assert!(pool.try_get().is_none());
+        object.release().await.unwrap();
+
+        assert!(pool.try_get().is_none());
+        let mut cancelled = Box::pin(pool.get());
+        assert!(poll!(&mut cancelled).is_pending());
+        drop(cancelled);
+        assert_eq!((0, 1), (pool.stats().in_use, pool.stats().idle));
+        pool.get().await.release().await.unwrap();
+    }
+
+    #[test(tokio::test(flavor = "multi_thread", worker_threads = 4))]
+    async fn concurrent_releases_never_fail() {
+        let pool = Arc::new(Pool::new(64, Box::new(AnyObject::new)).await.unwrap());
+        let objects: Vec<_> = pool.try_get_n(64);
+        assert_eq!(64, objects.len());
+        let releases: Vec<_> = objects
+            .into_iter()
+            .map(|object| tokio::spawn(object.release()))
+            .collect();
+        for release in releases {
+            release.await.unwrap().unwrap();
+        }
+        assert_eq!((64, 0), (pool.stats().idle, pool.stats().in_use));
+    }
+
+    #[test(tokio::test)]
+    async fn detached_factory_makes_taints_reduce_capacity() {
+        let pool = Pool::new(3, Box::new(AnyObject::new)).await.unwrap();
+        pool.warm(2).await.unwrap();
+        pool.detach_factory().await;
+        assert_eq!(2, pool.stats().capacity);
+
+        let mut first = pool.get().await;
+        let second = pool.get().await;
+        assert!(pool.try_get().is_none());
+        first.tainted();
+        first.release().await.unwrap();
+        assert_eq!(1, pool.stats().capacity);
+        assert!(pool.try_get().is_none());
+        second.release().await.unwrap();
+        let member = get!(object = pool => object.member.clone());
+        assert_eq!(member, pool.get().await.member);
+        assert_eq!(1, pool.stats().capacity);
+
+        pool.attach_factory(SyncFactory::from(AnyObject::new)).await;
+        let mut object = pool.get().await;
+        object.tainted();
+        object.release().await.unwrap();
+        assert_eq!(1, pool.stats().capacity);
+        assert_ne!(member, pool.get().await.member);
+    }
+
+    /// Checks that `text` follows the Prometheus text exposition format.
+    fn assert_valid_exposition(text: &str) {
+        let mut typed = HashSet::new();
+        for line in text.lines() {
+            if let Some(comment) = line.strip_prefix("# ") {
+                let mut parts = comment.splitn(3, ' ');
+                let (keyword, name) = (parts.next().unwrap(), parts.next().unwrap());
+                assert!(parts.next().is_some(), "incomplete comment: {line}");
+                if keyword == "TYPE" {
+                    typed.insert(name.to_owned());
+                }
+                continue;
+            }
+            let (series, value) = line.rsplit_once(' ').unwrap();
+            assert!(value.parse::<f64>().is_ok(), "invalid value: {line}");
+            let (name, labels) = series.split_once('{').unwrap();
+            let family = name.trim_end_matches("_sum").trim_end_matches("_count");
+            assert!(typed.contains(family), "sample without type: {line}");
+            assert!(name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'));
+            let labels = labels.strip_suffix('}').unwrap();
+            for label in labels.split(',') {
+                let (key, value) = label.split_once('=').unwrap();
+                assert!(key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'));
+                assert!(value.starts_with('"') && value.ends_with('"'));
+            }
+        }
+    }
+
+    #[test(tokio::test)]
+    async fn export_prometheus_reports_pool_statistics() {
+        let pool = Pool::builder()
+            .name("exported")
+            .size(3)
+            .factory(SyncFactory::from(AnyObject::new))
+            .build()
+            .await
+            .unwrap();
+        assert_valid_exposition(&pool.export_prometheus());
+
+        let first = pool.get().await;
+        let second = pool.get().await;
+        first.release().await.unwrap();
+        let text = pool.export_prometheus();
+        assert_valid_exposition(&text);
+        assert!(text.contains("lazy_pool_idle_objects{pool=\"exported\"} 1\n"));
+        assert!(text.contains("lazy_pool_in_use_objects{pool=\"exported\"} 1\n"));
+        assert!(text.contains("lazy_pool_total_creates_total{pool=\"exported\"} 2\n"));
+        for quantile in ["0.5", "0.95", "0.99"] {
+            let series =
+                format!("lazy_pool_wait_seconds{{pool=\"exported\",quantile=\"{quantile}\"}}");
+            assert!(text.contains(&series));
+        }
+        assert!(text.contains("lazy_pool_wait_seconds_count{pool=\"exported\"} 2\n"));
+        second.release().await.unwrap();
+    }
+
+    #[test(tokio::test)]
+    async fn reentrant_gets_fail_within_the_same_task() {
+        let pool = Arc::new(Pool::new(1, Box::new(AnyObject::new)).await.unwrap());
+        let object = pool.get_exclusive_with_reentrancy_guard().await.unwrap();
+        let nested = async { pool.get_exclusive_with_reentrancy_guard().await };
+        assert!(matches!(nested.await, Err(LazyPoolError::Reentrant)));
+
+        let other_pool = pool.clone();
+        let other_task = tokio::spawn(async move {
+            let object = other_pool.get_exclusive_with_reentrancy_guard().await;
+            object.map(|object| object.member.clone())
+        });
+        Delay::new(Duration::from_millis(10)).await;
+        assert!(!other_task.is_finished());
+        let member = object.member.clone();
+        object.release().await.unwrap();
+        assert_eq!(member, other_task.await.unwrap().unwrap());
+
+        let object = pool.get_exclusive_with_reentrancy_guard().await.unwrap();
+        drop(object);
+        assert!(pool.get_exclusive_with_reentrancy_guard().await.is_ok());
+    }
+
+    #[derive(Default)]
+    struct RecordingObserver {
+        events: SyncMutex<Vec<String>>,
+    }
+
+    impl PoolObserver<AnyObject> for Arc<RecordingObserver> {
+        fn on_checkout(&self, item: &AnyObject, from: CheckoutSource) {
+            let event = format!("checkout {} {from:?}", item.member);
+            self.events.lock().unwrap().push(event);
+        }
+
+        fn on_return(&self, item: &AnyObject, tainted: bool) {
+            let event = format!("return {} {tainted}", item.member);
+            self.events.lock().unwrap().push(event);
+        }
+    }
+
+    #[test(tokio::test)]
+    async fn observer_sees_checkout_and_release() {
+        let pool = Pool::builder()
+            .size(1)
+            .factory(SyncFactory::from(|| AnyObject::with_context("observed")))
+            .with_return_validator(|item: &AnyObject| item.member == "observed")
+            .build()
+            .await
+            .unwrap();
+        let observer = Arc::new(RecordingObserver::default());
+        pool.get_with_observer(observer.clone())
+            .await
+            .release()
+            .await
+            .unwrap();
+        let mut object = pool.get_with_observer(observer.clone()).await;
+        object.member = String::from("invalid");
+        drop(object);
+        get!(object = pool => assert_eq!("observed", object.member));
+        assert_eq!(
+            vec![
+                "checkout observed Created",
+                "return observed false",
+                "checkout observed Idle",
+                "return invalid true",
+            ],
+            *observer.events.lock().unwrap()
+        );
+    }
+
+    #[test(tokio::test)]
+    async fn return_callbacks_run_once_even_for_tainted_objects() {
+        let pool = Pool::new(1, Box::new(AnyObject::new)).await.unwrap();
+        let returned = Arc::new(SyncMutex::new(Vec::new()));
+
+        let callback_returned = returned.clone();
+        let object = pool
+            .get_async_callback(move |item: &AnyObject| {
+                callback_returned.lock().unwrap().push(item.member.clone());
+            })
+            .await;
+        let first = object.member.clone();
+        object.release().await.unwrap();
+
+        let callback_returned = returned.clone();
+        let mut object = pool
+            .get_async_callback(move |item: &AnyObject| {
+                callback_returned.lock().unwrap().push(item.member.clone());
+            })
+            .await;
+        object.tainted();
+        object.release().await.unwrap();
+        get!(object = pool => {});
+
+        assert_eq!(vec![first.clone(), first], *returned.lock().unwrap());
+    }
+
+    trait Counter: Send {
+        fn increment(&mut self) -> usize;
+    }
+
+    impl Counter for usize {
+        fn increment(&mut self) -> usize {
+            *self += 1;
+            *self
+        }
+    }
+
+    #[test(tokio::test)]
+    async fn get_as_views_boxed_trait_objects_as_the_trait() {
+        let pool = Pool::new(1, Box::new(|| Box::new(0usize) as Box<dyn Counter>))
+            .await
+            .unwrap();
+        let mut counter = pool.get_as::<dyn Counter>().await;
+        let counter_ref: &mut dyn Counter = &mut *counter;
+        assert_eq!(1, counter_ref.increment());
+        counter.release().await.unwrap();
+
+        let mut counter = pool.get_as::<dyn Counter>().await;
+        assert_eq!(2, counter.increment());
+        counter.tainted();
+        counter.release().await.unwrap();
+
+        let strings = Pool::new(1, Box::new(|| String::from("text")))
+            .await
+            .unwrap();
+        let text = strings.get_as::<str>().await;
+        assert_eq!(4, text.len());
+        text.into_inner().release().await.unwrap();
+    }
+
+    #[test(tokio::test)]
+    async fn get_until_success_replaces_objects_until_one_succeeds() {
+        let created = Arc::new(AtomicUsize::new(0));
+        let counter = created.clone();
+        let pool = Pool::new(1, Box::new(move || counter.fetch_add(1, Ordering::SeqCst)))
+            .await
+            .unwrap();
+        let attempts = AtomicUsize::new(0);
+        let result = pool
+            .get_until_success(
+                |generation| {
+                    attempts.fetch_add(1, Ordering::SeqCst);
+                    let generation = *generation;
+                    async move {
+                        if generation < 2 {
+                            Err(format!("stale object {generation}"))
+                        } else {
+                            Ok(generation)
+                        }
+                    }
+                },
+                3,
+            )
+            .await;
+        assert_eq!(Ok(2), result);
+        assert_eq!(3, attempts.load(Ordering::SeqCst));
+        assert_eq!(2, *pool.get().await);
+
+        let result = pool
+            .get_until_success(|_| async { StdResult::<(), _>::Err("always") }, 2)
+            .await;
+        assert_eq!(Err("always"), result);
+        assert_eq!(4, created.load(Ordering::SeqCst));
+    }
+
+    #[test(tokio::test)]
+    async fn changed_max_idle_duration_applies_to_next_eviction() {
+        let pool = Arc::new(
+            Pool::builder()
+                .size(2)
+                .min_idle(2)
+                .factory(SyncFactory::from(AnyObject::new))
+                .with_max_idle_duration(Duration::from_secs(3600))
+                .build()
+                .await
+                .unwrap(),
+        );
+        let evictions = Arc::new(AtomicUsize::new(0));
+        let counter = evictions.clone();
+        pool.monitor().on_evict(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+        let evicting_pool = pool.clone();
+        let eviction =
+            tokio::spawn(async move { evicting_pool.evict_idle(Duration::from_millis(10)).await });
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(2, pool.stats().idle);
+        assert_eq!(0, evictions.load(Ordering::SeqCst));
+
+        pool.set_max_idle_duration(Duration::from_millis(1));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        eviction.abort();
+        assert_eq!(0, pool.stats().idle);
+        assert_eq!(2, evictions.load(Ordering::SeqCst));
+        assert_eq!(2, pool.stats().capacity);
+        pool.get().await.release().await.unwrap();
+    }
+
+    #[test(tokio::test)]
+    async fn shorter_health_check_interval_applies_immediately() {
+        let checks = Arc::new(AtomicUsize::new(0));
+        let counter = checks.clone();
+        let pool = Arc::new(
+            Pool::builder()
+                .size(1)
+                .min_idle(1)
+                .factory(SyncFactory::from(AnyObject::new))
+                .with_return_validator(move |_: &AnyObject| {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    true
+                })
+                .with_health_check_interval(Duration::from_secs(3600))
+                .build()
+                .await
+                .unwrap(),
+        );
+        let checking_pool = pool.clone();
+        let checking = tokio::spawn(async move { checking_pool.check_health().await });
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(0, checks.load(Ordering::SeqCst));
+
+        pool.set_health_check_interval(Duration::from_millis(5));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        checking.abort();
+        assert!(checks.load(Ordering::SeqCst) >= 5);
+        assert_eq!(1, pool.stats().idle);
+    }
+
+    #[cfg(feature = "opentelemetry")]
+    #[test(tokio::test)]
+    async fn checkout_span_is_child_of_given_context() {
+        use opentelemetry::{
+            global,
+            trace::{Span, TraceContextExt, Tracer},
+            Context, Value,
+        };
+
+        let (tracer_provider, spans) = exported_spans();
+        let pool = Pool::builder()
+            .with_telemetry_prefix("span_context_test")
+            .size(1)
+            .factory(SyncFactory::from(AnyObject::new))
+            .build()
+            .await
+            .unwrap();
+        let parent = global::tracer("span_context_test").start("request");
+        let parent_id = parent.span_context().span_id();
+        let context = Context::current_with_span(parent);
+
+        let object = pool.get_with_span_context(context.clone()).await;
+        let finished = || {
+            tracer_provider.force_flush().unwrap();
+            spans
+                .get_finished_spans()
+                .unwrap()
+                .into_iter()
+                .find(|span| span.name == "span_context_test.pool.checkout")
+        };
+        assert!(finished().is_none());
+        object.release().await.unwrap();
+        context.span().end();
+
+        let checkout = finished().unwrap();
+        assert_eq!(parent_id, checkout.parent_span_id);
+        let wait = checkout
+            .attributes
+            .iter()
+            .find(|attribute| attribute.key.as_str() == "pool.wait_duration")
+            .map(|attribute| attribute.value.clone());
+        assert!(matches!(wait, Some(Value::F64(seconds)) if seconds >= 0.0));
+    }
+
+    #[cfg(feature = "background-factory")]
+    #[test(tokio::test)]
+    async fn nonblocking_factory_lets_other_checkouts_reuse_objects() {
+        let pool = Arc::new(Pool::new_with_factory(2, SlowFactory {}).await.unwrap());
+        let object = pool.get().await;
+
+        let creating_pool = pool.clone();
+        let creating = tokio::spawn(async move {
+            creating_pool
+                .get_nonblocking_factory()
+                .await
+                .release()
+                .await
+                .unwrap()
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        object.release().await.unwrap();
+        let started = Instant::now();
+        let reused = pool.get().await;
+        assert!(started.elapsed() < Duration::from_millis(50));
+        reused.release().await.unwrap();
+        creating.await.unwrap();
+        assert_eq!(2, pool.stats().idle);
+    }
+
+    #[cfg(feature = "background-factory")]
+    #[test(tokio::test)]
+    async fn nonblocking_factory_creates_objects_concurrently() {
+        let pool = Pool::builder()
+            .size(3)
+            .background_factory(|| async {
+                Delay::new(Duration::from_millis(50)).await;
+                AnyObject::new()
+            })
+            .build()
+            .await
+            .unwrap();
+        let started = Instant::now();
+        let (a, b, c) = futures::join!(
+            pool.get_nonblocking_factory(),
+            pool.get_nonblocking_factory(),
+            pool.get_nonblocking_factory(),
+        );
+        let elapsed = started.elapsed();
+        assert!(elapsed >= Duration::from_millis(50));
+        assert!(elapsed < Duration::from_millis(120), "took {elapsed:?}");
+        assert_eq!(3, pool.stats().in_use);
+        drop((a, b, c));
+        assert_eq!(3, pool.stats().idle);
+    }
+
+    #[cfg(feature = "test-utils")]
+    #[test(tokio::test(flavor = "multi_thread"))]
+    async fn stress_test_reports_no_errors_for_a_healthy_pool() {
+        let pool = Pool::new(4, Box::new(AnyObject::new))
+            .await
+            .unwrap()
+            .into_shared();
+        let report = pool.stress_test(1000, 8).await;
+        assert_eq!(0, report.error_count);
+        assert!(report.throughput_ops_per_sec > 0.0);
+        assert_eq!(4, pool.stats().capacity);
+        assert_eq!(0, pool.stats().in_use);
+    }
+
+    #[test(tokio::test)]
+    async fn telemetry_context_records_checkout_cycle() {
+        struct Recorded(SyncMutex<Vec<String>>);
+
+        struct RecordingSpan {
+            name: String,
+            recorded: Arc<Recorded>,
+        }
+
+        impl Span for RecordingSpan {
+            fn set_attribute(&mut self, key: &str, _: f64) {
+                let entry = format!("attribute {} {key}", self.name);
+                self.recorded.0.lock().unwrap().push(entry);
+            }
+
+            fn end(&mut self) {
+                let entry = format!("end {}", self.name);
+                self.recorded.0.lock().unwrap().push(entry);
+            }
+        }
+
+        struct RecordingContext(Arc<Recorded>);
+
+        impl TelemetryContext for RecordingContext {
+            fn start_span(&self, name: &str) -> Box<dyn Span> {
+                self.0 .0.lock().unwrap().push(format!("start {name}"));
+                Box::new(RecordingSpan {
+                    name: name.to_owned(),
+                    recorded: self.0.clone(),
+                })
+            }
+
+            fn record_metric(&self, name: &str, value: f64) {
+                assert!(value >= 0.0);
+                self.0 .0.lock().unwrap().push(format!("metric {name}"));
+            }
+        }
+
+        let recorded = Arc::new(Recorded(SyncMutex::new(Vec::new())));
+        let pool = Pool::new(1, Box::new(AnyObject::new)).await.unwrap();
+        let object = pool
+            .get_with_telemetry_context(RecordingContext(recorded.clone()))
+            .await;
+        assert_eq!(3, recorded.0.lock().unwrap().len());
+        object.release().await.unwrap();
+
+        assert_eq!(
+            vec![
+                "start pool.checkout",
+                "metric pool.wait_duration",
+                "attribute pool.checkout pool.wait_duration",
+                "metric pool.use_duration",
+                "end pool.checkout",
+            ],
+            *recorded.0.lock().unwrap()
+        );
+        let object = pool.get_with_telemetry_context(()).await;
+        object.release().await.unwrap();
+    }
+
+    #[test(tokio::test)]
+    async fn release_task_releases_dropped_guard() {
+        let pool = Pool::new(1, Box::new(AnyObject::new)).await.unwrap();
+        let (mut object, release) = pool.get_returning_guard().await;
+        let release = tokio::spawn(release);
+        object.member = String::from("returned");
+        drop(object);
+        release.await.unwrap().unwrap();
+        assert_eq!(1, pool.stats().idle);
+        assert_eq!("returned", pool.get().await.member);
+
+        let (mut object, release) = pool.get_returning_guard().await;
+        object.tainted();
+        drop(object);
+        release.await.unwrap();
+        assert_eq!(0, pool.stats().idle);
+        assert_ne!("returned", pool.get().await.member);
+    }
+
+    #[test(tokio::test)]
+    async fn failed_transaction_replaces_object() {
+        let pool = Pool::builder()
+            .name("transactions")
+            .size(1)
+            .factory(SyncFactory::from(AnyObject::new))
+            .build()
+            .await
+            .unwrap();
+        let member = pool
+            .get_transactional(|item| {
+                let member = item.member.clone();
+                async { Ok(member) }
+            })
+            .await
+            .unwrap();
+        let object = pool.get().await;
+        let id = object.object_id();
+        assert_eq!(member, object.member);
+        object.release().await.unwrap();
+
+        let err = pool
+            .get_transactional(|item| {
+                item.member = String::from("half written");
+                async { Err::<(), _>("constraint violated".into()) }
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(Some(id), err.context.object_id);
+        assert_eq!("constraint violated", err.source.to_string());
+        assert_eq!(
+            format!("[transactions] transaction failed (id={id})"),
+            err.to_string()
+        );
+
+        let object = pool.get().await;
+        assert_ne!(id, object.object_id());
+        assert_ne!(member, object.member);
+        assert_ne!("half written", object.member);
+        object.release().await.unwrap();
+    }
+
+    #[test(tokio::test)]
+    async fn stateful_sync_factory_produces_increasing_values() {
+        let mut counter = 0;
+        let pool = Pool::new(3, move || {
+            counter += 1;
+            counter
+        })
+        .await
+        .unwrap();
+        let objects = [pool.get().await, pool.get().await, pool.get().await];
+        let values: Vec<_> = objects.iter().map(|object| **object).collect();
+        assert_eq!(vec![1, 2, 3], values);
+    }
+
+    #[test(tokio::test)]
+    async fn middleware_layers_run_in_order() {
+        type Calls = Arc<SyncMutex<Vec<String>>>;
+
+        struct Recording(&'static str, Calls);
+
+        impl PoolMiddleware<AnyObject> for Recording {
+            async fn pre_checkout(&self, pool: &Pool<AnyObject>) -> Result<()> {
+                let in_use = pool.stats().in_use;
+                self.1
+                    .lock()
+                    .unwrap()
+                    .push(format!("{} pre {in_use}", self.0));
+                Ok(())
+            }
+
+            fn post_return(&self, _: &AnyObject, tainted: bool) {
+                self.1
+                    .lock()
+                    .unwrap()
+                    .push(format!("{} post {tainted}", self.0));
+            }
+        }
+
+        struct Quota;
+
+        impl PoolMiddleware<AnyObject> for Quota {
+            async fn pre_checkout(&self, _: &Pool<AnyObject>) -> Result<()> {
+                Err(LazyPoolError::Exhausted)
+            }
+        }
+
+        let calls = Calls::default();
+        let pool = Pool::new(1, Box::new(AnyObject::new)).await.unwrap();
+        let layers = (
+            Recording("outer", calls.clone()),
+            Recording("inner", calls.clone()),
+        );
+        let mut object = pool.get_with_middleware(layers).await.unwrap();
+        object.tainted();
+        object.release().await.unwrap();
+        assert_eq!(
+            vec![
+                "outer pre 0",
+                "inner pre 0",
+                "inner post true",
+                "outer post true"
+            ],
+            *calls.lock().unwrap()
+        );
+
+        let result = pool
+            .get_with_middleware((Recording("logged", calls.clone()), Quota))
+            .await;
+        assert!(matches!(result, Err(LazyPoolError::Exhausted)));
+        assert_eq!(0, pool.stats().in_use);
+        assert_eq!(5, calls.lock().unwrap().len());
+    }
+
+    #[test(tokio::test)]
+    async fn expired_lease_replaces_object() {
+        let pool = Pool::new(1, Box::new(AnyObject::new)).await.unwrap();
+        let object = pool.get_with_lease(Duration::from_secs(3600)).await;
+        let member = object.member.clone();
+        assert!(object.lease_remaining() > Duration::from_secs(3500));
+        object.release().await.unwrap();
+        assert_eq!(member, pool.get().await.member);
+
+        let object = pool.get_with_lease(Duration::from_millis(10)).await;
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(Duration::ZERO, object.lease_remaining());
+        object.release().await.unwrap();
+        assert_eq!(0, pool.stats().idle);
+        let replaced = pool.get().await.member.clone();
+        assert_ne!(member, replaced);
+
+        drop(pool.get_with_lease(Duration::ZERO).await);
+        assert_ne!(replaced, pool.get().await.member);
+    }
+
+    #[test(tokio::test)]
+    async fn usage_tracker_accumulates_usage_across_checkouts() {
+        #[derive(Default)]
+        struct Transferred {
+            checkouts: usize,
+            bytes: HashMap<String, u64>,
+        }
+
+        impl UsageTracker<AnyObject> for Transferred {
+            fn record_checkout(&mut self, _: &AnyObject) {
+                self.checkouts += 1;
+            }
+
+            fn record_return(&mut self, item: &AnyObject, usage: u64) {
+                *self.bytes.entry(item.member.clone()).or_default() += usage;
+            }
+        }
+
+        let pool = Pool::new(1, Box::new(AnyObject::new)).await.unwrap();
+        let mut tracker = Transferred::default();
+        let mut object = pool.get_with_usage_tracker(&mut tracker).await;
+        let member = object.member.clone();
+        object.report_usage(100);
+        object.report_usage(20);
+        object.release().await.unwrap();
+        let mut object = pool.get_with_usage_tracker(&mut tracker).await;
+        object.report_usage(3);
+        drop(object);
+        drop(pool.get_with_usage_tracker(&mut tracker).await);
+
+        assert_eq!(3, tracker.checkouts);
+        assert_eq!(HashMap::from([(member, 123)]), tracker.bytes);
     }
 
     #[test(tokio::test)]
-    async fn can_get_multiple_objects_from_pool() {
-        let pool = Pool::new(10, Box::new(AnyObject::new)).await.unwrap();
-        get!(item_1 = pool => {
-            get!(item_2 = pool => {
-                assert!(*item_1 != *item_2);
-            })
-        })
+    async fn exhausted_shard_does_not_fall_back_to_others() {
+        let mut shards = Vec::new();
+        for _ in 0..3 {
+            shards.push(Pool::new(1, Box::new(AnyObject::new)).await.unwrap());
+        }
+        let pool = ShardedPool::new(shards);
+        let object = pool.get_from_shard(0).await.unwrap();
+
+        assert!(pool.get_from_shard(0).now_or_never().is_none());
+        let other = pool.get_from_shard(1).await.unwrap();
+        assert!(matches!(
+            pool.get_from_shard(3).await,
+            Err(LazyPoolError::InvalidShard)
+        ));
+
+        object.release().await.unwrap();
+        other.release().await.unwrap();
+        pool.get_from_shard(0)
+            .await
+            .unwrap()
+            .release()
+            .await
+            .unwrap();
     }
 
     #[test(tokio::test)]
-    async fn item_is_relased_back_to_the_start_of_the_pool_when_dropped() {
-        let pool = Pool::new(10, Box::new(AnyObject::new)).await.unwrap();
-        let member_name_1 = get!(item = pool => item.member.clone());
-        let member_name_2 = get!(item = pool => item.member.clone());
-        let member_name_3 = get!(item = pool => item.member.clone());
-        assert_eq!(member_name_1, member_name_2);
-        assert_eq!(member_name_2, member_name_3);
+    async fn profiled_checkout_breaks_down_timings() {
+        let pool = Pool::builder()
+            .size(1)
+            .factory(SlowFactory {})
+            .with_async_post_checkout_hook(|_| Delay::new(Duration::from_millis(20)).boxed())
+            .build()
+            .await
+            .unwrap();
+        let object = pool.get_with_profiling().await;
+        assert!(object.queue_wait() >= Duration::from_millis(100));
+        assert!(object.total_overhead() >= object.queue_wait() + Duration::from_millis(20));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(object.use_duration() >= Duration::from_millis(10));
+
+        let queue_wait = object.queue_wait();
+        let (result, profile) = object.release().await;
+        result.unwrap();
+        assert_eq!(queue_wait, profile.queue_wait);
+        assert!(profile.use_duration >= Duration::from_millis(10));
+        assert_eq!(1, pool.stats().idle);
     }
 
     #[test(tokio::test)]
-    async fn pool_does_not_go_over_capacity_when_running_out_of_items() {
-        let pool = Pool::new(2, Box::new(AnyObject::new)).await.unwrap();
-        get!(item_1 = pool => {
-            get!(item_2 = pool => {
-                let mut timeout = Delay::new(Duration::from_millis(100)).fuse();
-                select! {
-                    _ = pool.get().fuse() => panic!("should not be able to get this"),
-                    _ = timeout => {}
-                }
-            });
-            let mut timeout = Delay::new(Duration::from_millis(100)).fuse();
+    async fn invariants_hold_after_gets_and_releases() {
+        let pool = Pool::new(4, Box::new(AnyObject::new)).await.unwrap();
+        assert_eq!(Ok(()), pool.validate_invariants().await);
+
+        let first = pool.get().await;
+        let mut second = pool.get().await;
+        let third = pool.get().await;
+        assert_eq!(Ok(()), pool.validate_invariants().await);
+        first.release().await.unwrap();
+        second.tainted();
+        second.release().await.unwrap();
+        pool.resize(2);
+        assert_eq!(Ok(()), pool.validate_invariants().await);
+        third.release().await.unwrap();
+        pool.resize(5);
+        get!(object = pool => {});
+        assert_eq!(Ok(()), pool.validate_invariants().await);
+        assert_eq!(2, pool.stats().idle);
+
+        pool.usage.returning();
+        let violations = pool.validate_invariants().await.unwrap_err();
+        assert_eq!(
+            vec!["2 objects are idle, but the idle count is 3"],
+            violations
+        );
+    }
+
+    #[test(tokio::test)]
+    async fn cancelled_for_each_idle_puts_idle_objects_back() {
+        let pool = Pool::new(3, Box::new(AnyObject::new)).await.unwrap();
+        pool.warm(3).await.unwrap();
+        {
+            let applying = pool
+                .for_each_idle(|_| Delay::new(Duration::from_millis(100)))
+                .fuse();
+            let timeout = Delay::new(Duration::from_millis(20)).fuse();
+            futures::pin_mut!(applying, timeout);
             select! {
-                _ = pool.get().fuse() => {},
-                _ = timeout => panic!("should be able to get this"),
+                _ = applying => panic!("should have been cancelled"),
+                _ = timeout => {},
             }
-        });
+        }
+        assert_eq!(3, pool.stats().idle);
+        assert_eq!(3, pool.try_get_n(3).len());
     }
 
-    #[test]
-    fn can_share_pool_between_threads_in_sync_code() {
-        let pool = Arc::new(block_on(async {
-            Pool::new(3, Box::new(|| AnyObject::new())).await.unwrap()
-        }));
-        let members = Arc::new(SyncMutex::new(HashSet::<String>::new()));
-        let mut handles = vec![];
+    #[test(tokio::test)]
+    async fn reset_all_idle_clears_idle_objects_in_place() {
+        struct Session {
+            queries: usize,
+        }
 
-        for _ in 1..10 {
-            let local_pool = pool.clone();
-            let local_members = members.clone();
-            let handle = thread::spawn(move || {
-                let value = block_on(async {
-                    get!(item = local_pool => {
-                        Delay::new(Duration::from_millis(100)).await;
-                        item.member.clone()
-                    })
-                });
-                debug!("adding value to members");
-                local_members.lock().unwrap().insert(value);
-            });
-            handles.push(handle);
+        impl Reset for Session {
+            async fn reset(&mut self) {
+                Delay::new(Duration::from_millis(1)).await;
+                self.queries = 0;
+            }
         }
-        for handle in handles {
-            handle.join().unwrap();
+
+        let pool = Pool::new(3, Box::new(|| Session { queries: 0 }))
+            .await
+            .unwrap();
+        let mut objects = Vec::new();
+        for _ in 0..3 {
+            let mut object = pool.get().await;
+            object.queries = 5;
+            objects.push(object);
         }
-        assert_eq!(members.lock().unwrap().len(), 3);
+        let held = objects.pop().unwrap();
+        for object in objects {
+            object.release().await.unwrap();
+        }
+        let ids = pool.object_ids().await;
+
+        assert_eq!(2, pool.reset_all_idle().await);
+        assert_eq!(ids, pool.object_ids().await);
+        let first = pool.get().await;
+        let second = pool.get().await;
+        assert_eq!((0, 0), (first.queries, second.queries));
+        assert_eq!(5, held.queries);
     }
 
     #[test(tokio::test)]
-    async fn can_use_closure_as_factory() {
-        let context = "hello";
-        let pool = Pool::new(10, Box::new(move || AnyObject::with_context(context)))
-            .await
-            .unwrap();
-        assert_eq!(
-            AnyObject {
-                member: String::from("hello")
-            },
-            get!(item = pool => item.clone())
-        )
+    async fn cancelled_reset_all_idle_puts_idle_objects_back() {
+        struct Slow;
+
+        impl Reset for Slow {
+            async fn reset(&mut self) {
+                Delay::new(Duration::from_millis(100)).await;
+            }
+        }
+
+        let pool = Pool::new(3, Box::new(|| Slow)).await.unwrap();
+        pool.warm(3).await.unwrap();
+        {
+            let resetting = pool.reset_all_idle().fuse();
+            let timeout = Delay::new(Duration::from_millis(20)).fuse();
+            futures::pin_mut!(resetting, timeout);
+            select! {
+                _ = resetting => panic!("should have been cancelled"),
+                _ = timeout => {},
+            }
+        }
+        assert_eq!(3, pool.stats().idle);
+        assert_eq!(3, pool.try_get_n(3).len());
     }
 
-    struct AsyncFactory {}
+    #[test(tokio::test)]
+    async fn split_by_takes_matching_idle_objects() {
+        let pool = Pool::new_eager(5, Box::new(AnyObject::new)).await.unwrap();
+        let ids = pool.object_ids().await;
+        let mut members = Vec::new();
+        for _ in 0..5 {
+            let object = pool.get().await;
+            members.push(object.member.clone());
+            object.release().await.unwrap();
+        }
+        let selected = [members[1].clone(), members[3].clone()];
 
-    impl AsyncFactory {
-        async fn get_instance(&self) -> AnyObject {
-            AnyObject {
-                member: String::from("hello"),
+        let (matched, unmatched) = pool.split_by(|item| selected.contains(&item.member)).await;
+        assert!(unmatched.is_empty());
+        let matched: Vec<_> = matched.into_iter().map(|item| item.member).collect();
+        assert_eq!(selected.to_vec(), matched);
+        assert_eq!(3, pool.stats().idle);
+        assert_eq!(5, pool.stats().capacity);
+        let remaining = pool.object_ids().await;
+        assert_eq!(vec![ids[0], ids[2], ids[4]], remaining);
+        assert_eq!(Ok(()), pool.validate_invariants().await);
+    }
+
+    #[test(tokio::test)]
+    async fn wrapped_objects_round_trip_through_pool() {
+        struct Audited {
+            inner: AnyObject,
+            uses: usize,
+        }
+
+        impl Wrap<AnyObject> for Audited {
+            fn wrap(inner: AnyObject) -> Self {
+                Self { inner, uses: 0 }
+            }
+
+            fn unwrap(self) -> AnyObject {
+                let mut inner = self.inner;
+                inner.member = format!("{} {}", inner.member, self.uses);
+                inner
             }
         }
+
+        let pool = Pool::new(1, Box::new(|| AnyObject::with_context("wrapped")))
+            .await
+            .unwrap();
+        let wrapped = pool.with_object_wrapper::<Audited>();
+        let mut object = wrapped.get().await;
+        let id = object.object_id();
+        object.uses += 2;
+        assert_eq!("wrapped", object.inner.member);
+        object.release().await.unwrap();
+        assert_eq!(1, pool.stats().idle);
+
+        let mut object = wrapped.try_get().unwrap();
+        assert_eq!(id, object.object_id());
+        assert_eq!("wrapped 2", object.inner.member);
+        object.uses += 1;
+        drop(object);
+        let object = pool.get().await;
+        assert_eq!(id, object.object_id());
+        assert_eq!("wrapped 2 1", object.member);
     }
 
-    impl Factory<AnyObject> for AsyncFactory {
-        fn produce(&mut self) -> Box<dyn Future<Output = AnyObject> + Send + Unpin + '_> {
-            Box::new(Box::pin(self.get_instance()))
+    #[test(tokio::test)]
+    async fn get_n_distinct_hands_out_distinct_objects() {
+        let pool = Pool::new(3, Box::new(AnyObject::new)).await.unwrap();
+        let objects = pool.get_n_distinct(3).await.unwrap();
+        let ids: HashSet<_> = objects.iter().map(Pooled::object_id).collect();
+        assert_eq!(3, ids.len());
+        for object in objects {
+            object.release().await.unwrap();
         }
+
+        assert!(matches!(
+            pool.get_n_distinct(4).await,
+            Err(LazyPoolError::InsufficientCapacity)
+        ));
+        assert_eq!(0, pool.stats().in_use);
+        assert!(pool.get_n_distinct(0).await.unwrap().is_empty());
     }
 
     #[test(tokio::test)]
-    async fn can_use_async_function_as_factory() {
-        let pool = Pool::new_with_factory(10, AsyncFactory {}).await.unwrap();
-        assert_eq!(
-            AnyObject {
-                member: String::from("hello")
-            },
-            get!(item = pool => item.clone())
-        )
+    async fn evict_oldest_n_replaces_oldest_objects() {
+        let mut created = 0;
+        let pool = Pool::new(5, move || {
+            created += 1;
+            created
+        })
+        .await
+        .unwrap();
+        let mut objects = Vec::new();
+        for _ in 0..5 {
+            objects.push(pool.get().await);
+            tokio::time::sleep(Duration::from_millis(2)).await;
+        }
+        // Released newest first, so that age and idle order differ.
+        for object in objects.into_iter().rev() {
+            object.release().await.unwrap();
+        }
+
+        assert_eq!(2, pool.evict_oldest_n(2).await);
+        assert_eq!(3, pool.stats().idle);
+        let remaining = pool.try_get_n(3);
+        let values: Vec<_> = remaining.iter().map(|object| **object).collect();
+        assert_eq!(vec![5, 4, 3], values);
+        assert_eq!(6, *pool.get().await);
+        drop(remaining);
+        assert_eq!(4, pool.evict_oldest_n(10).await);
+        assert_eq!(0, pool.stats().idle);
     }
 
-    struct AsyncPoolHolder {
-        pool: Pool<AnyObject>,
+    #[test(tokio::test)]
+    async fn get_with_quota_limits_each_caller() {
+        let pool = Pool::new(5, Box::new(AnyObject::new)).await.unwrap();
+        let quota = MaxCheckoutsQuota::new(2);
+        let first = pool.get_with_quota("alice", quota.clone()).await.unwrap();
+        let second = pool.get_with_quota("alice", quota.clone()).await.unwrap();
+        assert!(matches!(
+            pool.get_with_quota("alice", quota.clone()).await,
+            Err(LazyPoolError::QuotaExceeded)
+        ));
+        let other = pool.get_with_quota("bob", quota.clone()).await.unwrap();
+        assert_eq!(2, quota.checkouts("alice"));
+        assert_eq!(1, quota.checkouts("bob"));
+
+        first.release().await.unwrap();
+        drop(other);
+        assert_eq!(1, quota.checkouts("alice"));
+        assert_eq!(0, quota.checkouts("bob"));
+        let third = pool.get_with_quota("alice", quota.clone()).await.unwrap();
+        assert_eq!(2, pool.stats().in_use);
+        drop((second, third));
     }
 
-    impl AsyncPoolHolder {
-        async fn new() -> Self {
-            Self {
-                pool: Pool::new(3, Box::new(|| AnyObject::new())).await.unwrap(),
-            }
+    #[test(tokio::test)]
+    async fn get_with_quota_reserves_before_waiting() {
+        let pool = Pool::new(1, Box::new(AnyObject::new)).await.unwrap();
+        let quota = MaxCheckoutsQuota::new(1);
+        let held = pool.get().await;
+        let mut first = Box::pin(pool.get_with_quota("alice", quota.clone()));
+        assert!(poll!(&mut first).is_pending());
+        assert_eq!(1, quota.checkouts("alice"));
+        assert!(matches!(
+            pool.get_with_quota("alice", quota.clone()).await,
+            Err(LazyPoolError::QuotaExceeded)
+        ));
+        drop(first);
+        assert_eq!(0, quota.checkouts("alice"));
+
+        let mut second = Box::pin(pool.get_with_quota("alice", quota.clone()));
+        assert!(poll!(&mut second).is_pending());
+        drop(held);
+        let second = second.await.unwrap();
+        assert_eq!(1, quota.checkouts("alice"));
+        drop(second);
+        assert_eq!(0, quota.checkouts("alice"));
+    }
+
+    #[test(tokio::test)]
+    async fn connection_error_handler_sees_factory_failures() {
+        let errors = Arc::new(SyncMutex::new(Vec::new()));
+        let handler_errors = errors.clone();
+        let pool = Pool::builder()
+            .size(1)
+            .try_factory(FailingFactory { failures: 2 })
+            .with_connection_error_handler(move |err| {
+                handler_errors.lock().unwrap().push(err);
+            })
+            .build()
+            .await
+            .unwrap();
+        get!(item = pool => assert!(!item.member.is_empty()));
+        let errors = errors.lock().unwrap();
+        assert_eq!(2, errors.len());
+        assert!(errors
+            .iter()
+            .all(|err| matches!(err, LazyPoolError::Factory(_))));
+    }
+
+    #[test(tokio::test)]
+    async fn get_checked_out_ids_lists_objects_in_flight() {
+        let pool = Pool::new(3, Box::new(AnyObject::new)).await.unwrap();
+        pool.warm(3).await.unwrap();
+        let first = pool.get().await;
+        let second = pool.get().await;
+        let ids: HashSet<_> = pool.get_checked_out_ids().into_iter().collect();
+        assert_eq!(HashSet::from([first.object_id(), second.object_id()]), ids);
+        assert_eq!(1, pool.object_ids().await.len());
+
+        first.release().await.unwrap();
+        assert_eq!(vec![second.object_id()], pool.get_checked_out_ids());
+        drop(second);
+        assert!(pool.get_checked_out_ids().is_empty());
+    }
+
+    #[test(tokio::test)]
+    async fn get_pooled_mut_releases_into_borrowed_pool() {
+        let pool = Pool::new(1, Box::new(AnyObject::new)).await.unwrap();
+        let mut object = pool.get_pooled_mut().await;
+        let id = object.object_id();
+        object.member.push_str(" changed");
+        assert_eq!(1, pool.stats().in_use);
+        assert_eq!(vec![id], pool.get_checked_out_ids());
+        drop(object);
+        assert_eq!(0, pool.stats().in_use);
+
+        let mut object = pool.get_pooled_mut().await;
+        assert_eq!(id, object.object_id());
+        assert!(object.member.ends_with(" changed"));
+        object.tainted();
+        object.release().await.unwrap();
+        let object = pool.get().await;
+        assert_ne!(id, object.object_id());
+    }
+
+    #[test(tokio::test)]
+    async fn get_with_error_budget_fails_fast_once_exhausted() {
+        let pool = Pool::new(1, Box::new(AnyObject::new)).await.unwrap();
+        let budget = ErrorBudget::new(2);
+        let timeout = Duration::from_millis(10);
+        let object = pool.get().await;
+        for _ in 0..2 {
+            assert!(matches!(
+                pool.get_with_error_budget(&budget, timeout).await,
+                Err(LazyPoolError::Timeout)
+            ));
         }
+        assert!(budget.is_exhausted());
+        object.release().await.unwrap();
 
-        async fn get_member_value(self) -> Vec<String> {
-            let mut values = vec![];
-            get!(object_1 = self.pool => {
-                get!(object_2 = self.pool => {
-                    get!(object_3 = self.pool => {
-                        values.push(object_1.member.clone());
-                        values.push(object_2.member.clone());
-                        values.push(object_3.member.clone());
-                    })
-                })
-            });
-            get!(object_4 = self.pool => {
-                get!(object_5 = self.pool => {
-                    get!(object_6 = self.pool => {
-                        values.push(object_4.member.clone());
-                        values.push(object_5.member.clone());
-                        values.push(object_6.member.clone());
-                    })
-                })
-            });
-            values
+        for _ in 0..3 {
+            assert!(matches!(
+                pool.get_with_error_budget(&budget, timeout).await,
+                Err(LazyPoolError::ErrorBudgetExhausted)
+            ));
         }
+        assert_eq!(0, pool.stats().in_use);
+        assert_eq!(0, budget.remaining());
+
+        budget.reset(1);
+        let object = pool.get_with_error_budget(&budget, timeout).await.unwrap();
+        assert_eq!(1, budget.remaining());
+        object.release().await.unwrap();
     }
 
+    #[cfg(feature = "cancellation")]
     #[test(tokio::test)]
-    async fn can_run_in_async() {
-        let holder = AsyncPoolHolder::new().await;
-        let res = holder.get_member_value().await;
-        let set: HashSet<String> = HashSet::from_iter(res.iter().cloned());
-        assert_eq!(3, set.len())
+    async fn scoped_checkout_gives_up_when_task_is_cancelled() {
+        let pool = Pool::new(1, Box::new(AnyObject::new))
+            .await
+            .unwrap()
+            .into_shared();
+        let object = pool.get().await;
+        let token = CancellationToken::new();
+        let task_pool = pool.clone();
+        let task = tokio::spawn(token.scope(async move {
+            task_pool
+                .get_scoped_to_tokio_context()
+                .await
+                .map(|object| object.object_id())
+        }));
+        tokio::task::yield_now().await;
+        token.cancel();
+        assert!(matches!(task.await.unwrap(), Err(LazyPoolError::Cancelled)));
+
+        // The cancelled checkout doesn't keep the pool locked.
+        let id = object.object_id();
+        object.release().await.unwrap();
+        let object = pool.get_scoped_to_tokio_context().await.unwrap();
+        assert_eq!(id, object.object_id());
     }
 
     #[test(tokio::test)]
-    async fn can_mutate_polled_items() {
-        struct Mutable {
-            count: i32,
+    async fn pool_hands_out_initial_objects_before_creating() {
+        let initial = vec![
+            AnyObject::with_context("initial 1"),
+            AnyObject::with_context("initial 2"),
+        ];
+        let pool = Pool::new_with_initial_objects(initial, 5, AnyObject::new)
+            .await
+            .unwrap();
+        assert_eq!(2, pool.stats().idle);
+        let mut objects = Vec::new();
+        for _ in 0..5 {
+            objects.push(pool.get().await);
         }
+        let members: Vec<_> = objects.iter().map(|object| &object.member[..]).collect();
+        assert_eq!(["initial 1", "initial 2"], members[..2]);
+        assert!(!members[2..]
+            .iter()
+            .any(|member| member.starts_with("initial")));
+        assert!(pool.try_get().is_none());
+        drop(objects);
 
-        let pool = Pool::new(2, Box::new(|| Mutable { count: 0 }))
+        let too_many = (0..3).map(|_| AnyObject::new()).collect();
+        assert!(matches!(
+            Pool::new_with_initial_objects(too_many, 2, AnyObject::new).await,
+            Err(LazyPoolError::InsufficientCapacity)
+        ));
+    }
+
+    #[test(tokio::test)]
+    async fn backpressure_signal_delays_checkouts() {
+        let delay = Duration::from_millis(50);
+        let pool = Pool::builder()
+            .size(1)
+            .factory(SyncFactory::from(AnyObject::new))
+            .with_backpressure_delay(delay)
+            .build()
             .await
             .unwrap();
+        let signal = Arc::new(AtomicBool::new(false));
+        let started = Instant::now();
+        drop(pool.get_with_backpressure_signal(signal.clone()).await);
+        assert!(started.elapsed() < delay);
 
-        get!(object = pool => {
-            object.count += 1;
-        });
+        signal.store(true, Ordering::Relaxed);
+        let started = Instant::now();
+        drop(pool.get_with_backpressure_signal(signal.clone()).await);
+        assert!(started.elapsed() >= delay);
+    }
 
-        get!(item_1 = pool => {
-            get!(item_2 = pool => {
-                assert_eq!(1, item_1.count);
-                assert_eq!(0, item_2.count);
-            })
-        })
+    #[test(tokio::test)]
+    async fn lru_eviction_drops_least_recently_returned_objects() {
+        let pool = Pool::builder()
+            .size(2)
+            .factory(SyncFactory::from(AnyObject::new))
+            .with_idle_lru_eviction()
+            .build()
+            .await
+            .unwrap();
+        let first = pool.get().await;
+        let second = pool.get().await;
+        let first_id = first.object_id();
+        second.release().await.unwrap();
+        Delay::new(Duration::from_millis(90)).await;
+        first.release().await.unwrap();
+        Delay::new(Duration::from_millis(10)).await;
+
+        // The object returned 100ms ago goes first, although it was created
+        // after the one returned 10ms ago.
+        assert_eq!(1, pool.evict_oldest_n(1).await);
+        assert_eq!(vec![first_id], pool.object_ids().await);
     }
 
     #[test(tokio::test)]
-    async fn marking_item_as_tainted_drops_it_from_pool() {
+    async fn pool_of_pools_hands_out_sub_pools() {
+        let pools = PoolOfPools::new(2, 3, AnyObject::new).await.unwrap();
+        let first = pools.get_sub_pool().await;
+        let second = pools.get_sub_pool().await;
+        assert_eq!(3, first.stats().capacity);
+        let objects = first.get_n_distinct(3).await.unwrap();
+        assert!(first.try_get().is_none());
+        let object = second.get().await;
+        let id = object.object_id();
+        drop(objects);
+        drop(object);
+        second.release().await.unwrap();
+
+        // The released sub-pool is handed out again, objects included.
+        let again = pools.get_sub_pool().await;
+        assert_eq!(vec![id], again.object_ids().await);
+        drop(first);
+    }
+
+    #[test(tokio::test)]
+    async fn dependency_is_dropped_when_object_is_released() {
+        struct Dependency(Arc<AtomicBool>);
+
+        impl Drop for Dependency {
+            fn drop(&mut self) {
+                self.0.store(true, Ordering::SeqCst);
+            }
+        }
+
         let pool = Pool::new(1, Box::new(AnyObject::new)).await.unwrap();
+        let dropped = Arc::new(AtomicBool::new(false));
+        let object = pool.get_with_dependency(Dependency(dropped.clone())).await;
+        let id = object.object_id();
+        assert!(!object.dep().0.load(Ordering::SeqCst));
+        assert_eq!(1, pool.stats().in_use);
+        object.release().await.unwrap();
+        assert!(dropped.load(Ordering::SeqCst));
+        assert_eq!(0, pool.stats().in_use);
+        assert_eq!(id, pool.get().await.object_id());
+    }
 
-        let mut item_val = String::new();
-        get!(item = pool => {
-            item_val += &item.member;
-        });
-        get!(item = pool => {
-            assert!(item_val == item.member);
-            Pooled::tainted(&mut item);
-        });
-        get!(item = pool => {
-            assert!(item_val != item.member);
-        });
+    #[test(tokio::test)]
+    async fn sequential_checkouts_have_consecutive_ids() {
+        let pool = Pool::new(2, Box::new(AnyObject::new)).await.unwrap();
+        let first = get!(item = pool => item.checkout_id());
+        let second = get!(item = pool => item.checkout_id());
+        assert_eq!(first + 1, second);
     }
 
     #[test(tokio::test)]
-    async fn can_run_in_multi_task_mode() {
-        let pool = Arc::new(Pool::new(1, Box::new(AnyObject::new)).await.unwrap());
+    async fn concurrent_checkouts_have_unique_ids() {
+        let pool = Arc::new(Pool::new(3, Box::new(AnyObject::new)).await.unwrap());
         let mut join_set = JoinSet::new();
-        for _ in 0..10 {
+        for _ in 0..20 {
             let local_pool = pool.clone();
-            join_set.spawn(async move {
-                get!(item = local_pool => {
-                    println!("{}", item.member);
-                });
-            });
+            join_set.spawn(async move { get!(item = local_pool => item.checkout_id()) });
         }
-        while !join_set.is_empty() {
-            join_set.join_next().await;
+        let mut ids = HashSet::new();
+        while let Some(id) = join_set.join_next().await {
+            assert!(ids.insert(id.unwrap()));
         }
+        assert_eq!(20, ids.len());
     }
 }
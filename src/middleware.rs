@@ -0,0 +1,51 @@
+use crate::{Pool, PoolObserver, Result};
+use std::future::{ready, Future};
+
+/**
+A layer around checkouts made through [`crate::Pool::get_with_middleware`],
+e.g. for logging or enforcing per-user quotas. Both methods do nothing by
+default.
+
+Middleware composes as a tuple: `(outer, inner)` runs the outer layer's
+[`PoolMiddleware::pre_checkout`] first and its
+[`PoolMiddleware::post_return`] last, like nested layers.
+*/
+pub trait PoolMiddleware<T: Send + 'static>: Send + Sync + 'static {
+    /// Runs before the object is checked out. Failing aborts the checkout.
+    fn pre_checkout(&self, _pool: &Pool<T>) -> impl Future<Output = Result<()>> + Send {
+        ready(Ok(()))
+    }
+
+    /// Runs when the object is released, after the return validator ran.
+    fn post_return(&self, _item: &T, _tainted: bool) {}
+}
+
+impl<T, A, B> PoolMiddleware<T> for (A, B)
+where
+    T: Send + 'static,
+    A: PoolMiddleware<T>,
+    B: PoolMiddleware<T>,
+{
+    async fn pre_checkout(&self, pool: &Pool<T>) -> Result<()> {
+        self.0.pre_checkout(pool).await?;
+        self.1.pre_checkout(pool).await
+    }
+
+    fn post_return(&self, item: &T, tainted: bool) {
+        self.1.post_return(item, tainted);
+        self.0.post_return(item, tainted);
+    }
+}
+
+/// Hooks middleware into the release of a checked out object.
+pub(crate) struct MiddlewareObserver<M>(pub(crate) M);
+
+impl<T, M> PoolObserver<T> for MiddlewareObserver<M>
+where
+    T: Send + 'static,
+    M: PoolMiddleware<T>,
+{
+    fn on_return(&self, item: &T, tainted: bool) {
+        self.0.post_return(item, tainted);
+    }
+}
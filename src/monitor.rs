@@ -0,0 +1,123 @@
+use std::{
+    sync::{Arc, RwLock},
+    time::SystemTime,
+};
+use uuid::Uuid;
+
+/// Lifecycle events of pooled objects.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PoolEvent {
+    /// An object was handed out by the pool.
+    Checkout,
+    /// An object was released back into the pool.
+    Return,
+    /// The factory created a new object.
+    Create,
+    /// An object was dropped by the pool, e.g. because it was tainted.
+    Evict,
+}
+
+/// Information passed to [`PoolMonitor`] callbacks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolEventData {
+    pub object_id: Uuid,
+    pub timestamp: SystemTime,
+    pub event: PoolEvent,
+}
+
+type Callback = Arc<dyn Fn(PoolEventData) + Send + Sync>;
+
+/// Callbacks registered through a [`PoolMonitor`].
+#[derive(Default)]
+pub(crate) struct Callbacks {
+    callbacks: RwLock<Vec<(PoolEvent, Callback)>>,
+}
+
+impl Callbacks {
+    pub(crate) fn notify(&self, event: PoolEvent, object_id: Uuid) {
+        let callbacks = self.callbacks.read().unwrap();
+        if callbacks.is_empty() {
+            return;
+        }
+        let data = PoolEventData {
+            object_id,
+            timestamp: SystemTime::now(),
+            event,
+        };
+        callbacks
+            .iter()
+            .filter(|(registered, _)| *registered == event)
+            .for_each(|(_, callback)| callback(data));
+    }
+
+    fn register(&self, event: PoolEvent, callback: Callback) {
+        self.callbacks.write().unwrap().push((event, callback));
+    }
+}
+
+/**
+Registers callbacks for the lifecycle events of a pool's objects, as
+returned by [`crate::Pool::monitor`]. Callbacks are invoked synchronously
+from within the pool's operations, so they must not block.
+
+```
+# use futures::executor::block_on;
+# use lazy_pool::{Pool, get};
+# use std::sync::{atomic::{AtomicUsize, Ordering}, Arc};
+
+# struct AnyObject;
+
+block_on(async {
+    let pool = Pool::new(2, Box::new(|| AnyObject)).await.unwrap();
+    let checkouts = Arc::new(AtomicUsize::new(0));
+    let counter = checkouts.clone();
+    pool.monitor().on_checkout(move |_| {
+        counter.fetch_add(1, Ordering::Relaxed);
+    });
+    get!(object = pool => {});
+    assert_eq!(1, checkouts.load(Ordering::Relaxed));
+});
+```
+*/
+#[derive(Clone)]
+pub struct PoolMonitor {
+    callbacks: Arc<Callbacks>,
+}
+
+impl PoolMonitor {
+    pub(crate) fn new(callbacks: Arc<Callbacks>) -> Self {
+        Self { callbacks }
+    }
+
+    /// Called when an object is handed out by the pool.
+    pub fn on_checkout<F>(&self, f: F)
+    where
+        F: Fn(PoolEventData) + Send + Sync + 'static,
+    {
+        self.callbacks.register(PoolEvent::Checkout, Arc::new(f));
+    }
+
+    /// Called when an object is released back into the pool.
+    pub fn on_return<F>(&self, f: F)
+    where
+        F: Fn(PoolEventData) + Send + Sync + 'static,
+    {
+        self.callbacks.register(PoolEvent::Return, Arc::new(f));
+    }
+
+    /// Called when the factory creates a new object.
+    pub fn on_create<F>(&self, f: F)
+    where
+        F: Fn(PoolEventData) + Send + Sync + 'static,
+    {
+        self.callbacks.register(PoolEvent::Create, Arc::new(f));
+    }
+
+    /// Called when an object is dropped by the pool instead of being reused.
+    pub fn on_evict<F>(&self, f: F)
+    where
+        F: Fn(PoolEventData) + Send + Sync + 'static,
+    {
+        self.callbacks.register(PoolEvent::Evict, Arc::new(f));
+    }
+}
@@ -0,0 +1,21 @@
+/// Where an object handed to a [`PoolObserver`] came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CheckoutSource {
+    /// The object had been used before and was waiting in the pool.
+    Idle,
+    /// The object was created for this checkout.
+    Created,
+}
+
+/**
+Observes a single checkout made through [`crate::Pool::get_with_observer`],
+e.g. for assertions in tests, without registering anything on the pool.
+Both methods do nothing by default.
+*/
+pub trait PoolObserver<T>: Send + Sync {
+    /// Called when the object is handed out.
+    fn on_checkout(&self, _item: &T, _from: CheckoutSource) {}
+
+    /// Called when the object is released, after the return validator ran.
+    fn on_return(&self, _item: &T, _tainted: bool) {}
+}
@@ -0,0 +1,74 @@
+use crate::{FactoryError, Pool, Pooled, Result, SyncFactory, TryFactory};
+use futures::FutureExt;
+use std::{future::Future, result::Result as StdResult};
+
+/**
+A pool of pools, for handing out a whole sub-pool to each task of a high
+throughput system, so that tasks don't contend with each other for the
+objects. Each sub-pool is a regular [`Pool`] of its own capacity, created
+lazily when a task first needs it and released like any other pooled
+object.
+
+```
+# use futures::executor::block_on;
+# use lazy_pool::PoolOfPools;
+
+# struct AnyObject;
+
+block_on(async {
+    let pools = PoolOfPools::new(4, 8, || AnyObject).await.unwrap();
+    let pool = pools.get_sub_pool().await;
+    let object = pool.get().await;
+    object.release().await.unwrap();
+    pool.release().await.unwrap();
+});
+```
+*/
+pub struct PoolOfPools<T: Send + 'static> {
+    pools: Pool<Pool<T>>,
+}
+
+impl<T: Send + 'static> PoolOfPools<T> {
+    /**
+    Creates a pool of up to `count` sub-pools, each holding up to
+    `sub_pool_size` objects created by a clone of `factory`.
+    */
+    pub async fn new<F>(count: usize, sub_pool_size: usize, factory: F) -> Result<Self>
+    where
+        F: Clone + Send + 'static,
+        SyncFactory<T>: From<F>,
+    {
+        let factory = SubPoolFactory {
+            sub_pool_size,
+            factory,
+        };
+        Ok(Self {
+            pools: Pool::new_with_try_factory(count, factory).await?,
+        })
+    }
+
+    /// Checks out a sub-pool, waiting for one to be released if all are in use.
+    pub async fn get_sub_pool(&self) -> Pooled<Pool<T>> {
+        self.pools.get().await
+    }
+}
+
+/// Creates the sub-pools of a [`PoolOfPools`].
+struct SubPoolFactory<F> {
+    sub_pool_size: usize,
+    factory: F,
+}
+
+impl<T, F> TryFactory<Pool<T>> for SubPoolFactory<F>
+where
+    T: Send + 'static,
+    F: Clone + Send + 'static,
+    SyncFactory<T>: From<F>,
+{
+    fn try_produce(
+        &mut self,
+    ) -> Box<dyn Future<Output = StdResult<Pool<T>, FactoryError>> + Unpin + Send + '_> {
+        let pool = Pool::new(self.sub_pool_size, self.factory.clone());
+        Box::new(Box::pin(pool).map(|pool| pool.map_err(FactoryError::from)))
+    }
+}
@@ -0,0 +1,51 @@
+use crate::{Pooled, Result};
+use std::{
+    borrow::{Borrow, BorrowMut},
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+};
+
+/**
+A checked out object viewed as a `U` it can be borrowed as, as returned by
+[`crate::Pool::get_as`]. Mostly useful for pools of boxed trait objects,
+which can be used as the trait object directly.
+*/
+pub struct PooledAs<T: Send + 'static, U: ?Sized> {
+    pooled: Pooled<T>,
+    view: PhantomData<fn(&U)>,
+}
+
+impl<T: Send + 'static, U: ?Sized> PooledAs<T, U> {
+    pub(crate) fn new(pooled: Pooled<T>) -> Self {
+        Self {
+            pooled,
+            view: PhantomData,
+        }
+    }
+
+    pub fn tainted(&mut self) {
+        self.pooled.tainted();
+    }
+
+    pub fn into_inner(self) -> Pooled<T> {
+        self.pooled
+    }
+
+    pub async fn release(self) -> Result<()> {
+        self.pooled.release().await
+    }
+}
+
+impl<T: Send + Borrow<U>, U: ?Sized> Deref for PooledAs<T, U> {
+    type Target = U;
+
+    fn deref(&self) -> &U {
+        (*self.pooled).borrow()
+    }
+}
+
+impl<T: Send + BorrowMut<U>, U: ?Sized> DerefMut for PooledAs<T, U> {
+    fn deref_mut(&mut self) -> &mut U {
+        (*self.pooled).borrow_mut()
+    }
+}
@@ -0,0 +1,74 @@
+use crate::{slot::Slot, Pool, Result};
+use std::ops::{Deref, DerefMut};
+use uuid::Uuid;
+
+/**
+An object checked out through [`crate::Pool::get_pooled_mut`], which
+borrows the pool instead of holding its own handles to it, so that the
+checkout doesn't clone any of them. Like [`crate::Pooled`], the object is
+released when the guard is dropped.
+*/
+pub struct PooledMut<'pool, T: Send + 'static> {
+    pool: &'pool Pool<T>,
+    wrapped: Option<Slot<T>>,
+    tainted: bool,
+    checkout_id: u64,
+}
+
+impl<'pool, T: Send> PooledMut<'pool, T> {
+    pub(crate) fn new(pool: &'pool Pool<T>, slot: Slot<T>, checkout_id: u64) -> Self {
+        Self {
+            pool,
+            wrapped: Some(slot),
+            tainted: false,
+            checkout_id,
+        }
+    }
+
+    pub fn tainted(&mut self) {
+        self.tainted = true;
+    }
+
+    pub fn checkout_id(&self) -> u64 {
+        self.checkout_id
+    }
+
+    pub fn object_id(&self) -> Uuid {
+        self.wrapped.as_ref().unwrap().id
+    }
+
+    pub async fn release(mut self) -> Result<()> {
+        self.return_to_pool()
+    }
+
+    fn return_to_pool(&mut self) -> Result<()> {
+        let slot = self.wrapped.take().unwrap();
+        self.pool
+            .return_borrowed(slot, self.tainted, self.checkout_id)
+    }
+}
+
+impl<T: Send> Drop for PooledMut<'_, T> {
+    fn drop(&mut self) {
+        if self.wrapped.is_none() {
+            return;
+        }
+        if let Err(err) = self.return_to_pool() {
+            log::error!("failed to release dropped object: {err:?}");
+        }
+    }
+}
+
+impl<T: Send> Deref for PooledMut<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.wrapped.as_ref().unwrap().item
+    }
+}
+
+impl<T: Send> DerefMut for PooledMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.wrapped.as_mut().unwrap().item
+    }
+}
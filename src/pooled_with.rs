@@ -0,0 +1,92 @@
+use crate::{Pooled, Result};
+use std::ops::{Deref, DerefMut};
+use uuid::Uuid;
+
+/**
+Per-checkout state which a [`PooledWith`] keeps next to its object, e.g.
+the expiry of a [`crate::LeasedPooled`], along with what to do with the
+object when it is checked out and released.
+*/
+pub trait CheckoutState<T: Send + 'static> {
+    /// What [`PooledWith::release`] resolves to.
+    type Released;
+
+    /// Called once the object has been checked out.
+    fn after_checkout(&mut self, _pooled: &Pooled<T>) {}
+
+    /// Called right before the object is released or dropped, e.g. to taint it.
+    fn before_release(&mut self, _pooled: &mut Pooled<T>) {}
+
+    /// Turns the outcome of releasing the object into what `release` resolves to.
+    fn released(&mut self, result: Result<()>) -> Self::Released;
+}
+
+/**
+A checked out object along with per-checkout state `S`, which the more
+specific checkouts such as [`crate::LeasedPooled`] and
+[`crate::TrackedPooled`] are made of. Like [`Pooled`], the object is
+released when this is dropped. The state is dropped right after.
+*/
+pub struct PooledWith<T: Send + 'static, S: CheckoutState<T>> {
+    pooled: Option<Pooled<T>>,
+    state: S,
+}
+
+impl<T: Send, S: CheckoutState<T>> PooledWith<T, S> {
+    pub(crate) fn new(pooled: Pooled<T>, mut state: S) -> Self {
+        state.after_checkout(&pooled);
+        Self {
+            pooled: Some(pooled),
+            state,
+        }
+    }
+
+    pub(crate) fn state(&self) -> &S {
+        &self.state
+    }
+
+    pub(crate) fn state_mut(&mut self) -> &mut S {
+        &mut self.state
+    }
+
+    pub fn tainted(&mut self) {
+        self.pooled.as_mut().unwrap().tainted();
+    }
+
+    pub fn object_id(&self) -> Uuid {
+        self.pooled.as_ref().unwrap().object_id()
+    }
+
+    pub async fn release(mut self) -> S::Released {
+        let result = self.take().release().await;
+        self.state.released(result)
+    }
+
+    fn take(&mut self) -> Pooled<T> {
+        let mut pooled = self.pooled.take().unwrap();
+        self.state.before_release(&mut pooled);
+        pooled
+    }
+}
+
+impl<T: Send, S: CheckoutState<T>> Drop for PooledWith<T, S> {
+    fn drop(&mut self) {
+        if self.pooled.is_some() {
+            drop(self.take());
+        }
+    }
+}
+
+impl<T: Send, S: CheckoutState<T>> Deref for PooledWith<T, S> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.pooled.as_ref().unwrap()
+    }
+}
+
+impl<T: Send, S: CheckoutState<T>> DerefMut for PooledWith<T, S> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.pooled.as_mut().unwrap()
+    }
+}
@@ -0,0 +1,30 @@
+use crate::{CheckoutState, PooledWith, Result};
+
+/**
+An object checked out through [`crate::Pool::get_with_dependency`],
+paired with a companion resource it needs while in use, e.g. a
+transaction on a pooled connection. The object is released as usual and
+the dependency is dropped right after.
+*/
+pub type PooledWithDep<T, D> = PooledWith<T, Dep<D>>;
+
+/// Companion resource of a [`PooledWithDep`] object.
+pub struct Dep<D>(pub(crate) D);
+
+impl<T: Send + 'static, D> CheckoutState<T> for Dep<D> {
+    type Released = Result<()>;
+
+    fn released(&mut self, result: Result<()>) -> Result<()> {
+        result
+    }
+}
+
+impl<T: Send, D> PooledWithDep<T, D> {
+    pub fn dep(&self) -> &D {
+        &self.state().0
+    }
+
+    pub fn dep_mut(&mut self) -> &mut D {
+        &mut self.state_mut().0
+    }
+}
@@ -0,0 +1,73 @@
+use crate::{CheckoutState, PooledWith, Result};
+use std::time::{Duration, Instant};
+
+/// Timings of a checkout made through [`crate::Pool::get_with_profiling`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CheckoutProfile {
+    /// Time from the start of the checkout until the pool handed out an object.
+    pub queue_wait: Duration,
+    /// Time from the start of the checkout until the object was usable,
+    /// including the post checkout hook.
+    pub total_overhead: Duration,
+    /// Time the object was held for.
+    pub use_duration: Duration,
+}
+
+/**
+An object checked out through [`crate::Pool::get_with_profiling`], which
+keeps track of when the checkout was queued, when the pool handed out the
+object and when the checkout completed. Releasing it resolves to the
+checkout's timings along with the outcome.
+*/
+pub type ProfiledPooled<T> = PooledWith<T, Profile>;
+
+/// Timestamps of a [`ProfiledPooled`] checkout.
+pub struct Profile {
+    queued_at: Instant,
+    dequeued_at: Instant,
+    checkout_at: Instant,
+}
+
+impl Profile {
+    pub(crate) fn new(queued_at: Instant, dequeued_at: Instant, checkout_at: Instant) -> Self {
+        Self {
+            queued_at,
+            dequeued_at,
+            checkout_at,
+        }
+    }
+
+    fn profile(&self) -> CheckoutProfile {
+        CheckoutProfile {
+            queue_wait: self.dequeued_at - self.queued_at,
+            total_overhead: self.checkout_at - self.queued_at,
+            use_duration: self.checkout_at.elapsed(),
+        }
+    }
+}
+
+impl<T: Send + 'static> CheckoutState<T> for Profile {
+    type Released = (Result<()>, CheckoutProfile);
+
+    fn released(&mut self, result: Result<()>) -> Self::Released {
+        (result, self.profile())
+    }
+}
+
+impl<T: Send> ProfiledPooled<T> {
+    pub fn queue_wait(&self) -> Duration {
+        self.profile().queue_wait
+    }
+
+    pub fn use_duration(&self) -> Duration {
+        self.profile().use_duration
+    }
+
+    pub fn total_overhead(&self) -> Duration {
+        self.profile().total_overhead
+    }
+
+    pub fn profile(&self) -> CheckoutProfile {
+        self.state().profile()
+    }
+}
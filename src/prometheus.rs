@@ -0,0 +1,69 @@
+use crate::{benchmark::percentile, PoolStats};
+use std::{fmt::Write, time::Duration};
+
+/// Pool statistics exported by [`crate::Pool::export_prometheus`].
+pub(crate) struct Metrics<'a> {
+    pub(crate) pool: Option<&'a str>,
+    pub(crate) stats: PoolStats,
+    pub(crate) total_creates: u64,
+    pub(crate) waits: Vec<Duration>,
+}
+
+impl Metrics<'_> {
+    /// Renders the metrics in the Prometheus text exposition format.
+    pub(crate) fn render(mut self) -> String {
+        let label = format!("pool=\"{}\"", escape(self.pool.unwrap_or_default()));
+        let mut out = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, value: String| {
+            let _ = writeln!(out, "# HELP {name} {help}");
+            let _ = writeln!(out, "# TYPE {name} {kind}");
+            let _ = writeln!(out, "{name}{{{label}}} {value}");
+        };
+        metric(
+            "lazy_pool_idle_objects",
+            "gauge",
+            "Objects waiting in the pool.",
+            self.stats.idle.to_string(),
+        );
+        metric(
+            "lazy_pool_in_use_objects",
+            "gauge",
+            "Objects checked out of the pool.",
+            self.stats.in_use.to_string(),
+        );
+        metric(
+            "lazy_pool_total_creates_total",
+            "counter",
+            "Objects created by the pool's factory.",
+            self.total_creates.to_string(),
+        );
+
+        self.waits.sort();
+        let name = "lazy_pool_wait_seconds";
+        let _ = writeln!(
+            out,
+            "# HELP {name} Time checkouts spent waiting for an object."
+        );
+        let _ = writeln!(out, "# TYPE {name} summary");
+        for (quantile, percent) in [("0.5", 50), ("0.95", 95), ("0.99", 99)] {
+            let wait = if self.waits.is_empty() {
+                f64::NAN
+            } else {
+                percentile(&self.waits, percent).as_secs_f64()
+            };
+            let _ = writeln!(out, "{name}{{{label},quantile=\"{quantile}\"}} {wait}");
+        }
+        let sum: Duration = self.waits.iter().sum();
+        let _ = writeln!(out, "{name}_sum{{{label}}} {}", sum.as_secs_f64());
+        let _ = writeln!(out, "{name}_count{{{label}}} {}", self.waits.len());
+        out
+    }
+}
+
+/// Escapes a label value as required by the exposition format.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -0,0 +1,155 @@
+use crate::PoolObserver;
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+/**
+Limits how many objects each caller may hold at once, for checkouts made
+through [`crate::Pool::get_with_quota`]. Implemented for [`Arc`]s of
+trackers, so that one tracker can be shared between checkouts.
+*/
+pub trait QuotaTracker: Send + Sync + 'static {
+    /// Whether `caller_id` may check out another object.
+    fn can_checkout(&self, caller_id: &str) -> bool;
+
+    /// Called when an object is handed out to `caller_id`.
+    fn record_checkout(&self, caller_id: &str);
+
+    /// Called when `caller_id` releases an object.
+    fn record_return(&self, caller_id: &str);
+
+    /**
+    Records a checkout for `caller_id` if [`QuotaTracker::can_checkout`]
+    allows it, returning whether it did. Called before waiting for an
+    object, so that concurrent checkouts can't all pass the check. The
+    default implementation checks and records separately; override it to
+    do both atomically.
+    */
+    fn try_reserve(&self, caller_id: &str) -> bool {
+        if !self.can_checkout(caller_id) {
+            return false;
+        }
+        self.record_checkout(caller_id);
+        true
+    }
+}
+
+impl<Q: QuotaTracker + ?Sized> QuotaTracker for Arc<Q> {
+    fn can_checkout(&self, caller_id: &str) -> bool {
+        (**self).can_checkout(caller_id)
+    }
+
+    fn record_checkout(&self, caller_id: &str) {
+        (**self).record_checkout(caller_id);
+    }
+
+    fn record_return(&self, caller_id: &str) {
+        (**self).record_return(caller_id);
+    }
+
+    fn try_reserve(&self, caller_id: &str) -> bool {
+        (**self).try_reserve(caller_id)
+    }
+}
+
+/**
+A [`QuotaTracker`] allowing each caller to hold at most `max` objects.
+Clones share their counts.
+*/
+#[derive(Clone, Debug)]
+pub struct MaxCheckoutsQuota {
+    max: usize,
+    counts: Arc<RwLock<HashMap<String, usize>>>,
+}
+
+impl MaxCheckoutsQuota {
+    pub fn new(max: usize) -> Self {
+        Self {
+            max,
+            counts: Arc::default(),
+        }
+    }
+
+    /// Number of objects `caller_id` currently holds.
+    pub fn checkouts(&self, caller_id: &str) -> usize {
+        let counts = self.counts.read().unwrap();
+        counts.get(caller_id).copied().unwrap_or(0)
+    }
+}
+
+impl QuotaTracker for MaxCheckoutsQuota {
+    fn can_checkout(&self, caller_id: &str) -> bool {
+        self.checkouts(caller_id) < self.max
+    }
+
+    fn record_checkout(&self, caller_id: &str) {
+        let mut counts = self.counts.write().unwrap();
+        *counts.entry(caller_id.to_owned()).or_default() += 1;
+    }
+
+    fn record_return(&self, caller_id: &str) {
+        let mut counts = self.counts.write().unwrap();
+        if let Some(count) = counts.get_mut(caller_id) {
+            *count -= 1;
+            if *count == 0 {
+                counts.remove(caller_id);
+            }
+        }
+    }
+
+    fn try_reserve(&self, caller_id: &str) -> bool {
+        let mut counts = self.counts.write().unwrap();
+        let count = counts.entry(caller_id.to_owned()).or_default();
+        if *count >= self.max {
+            if *count == 0 {
+                counts.remove(caller_id);
+            }
+            return false;
+        }
+        *count += 1;
+        true
+    }
+}
+
+/// Gives a caller's quota back when its object is released.
+pub(crate) struct QuotaObserver<Q> {
+    caller_id: String,
+    quota: Q,
+}
+
+impl<T, Q: QuotaTracker> PoolObserver<T> for QuotaObserver<Q> {
+    fn on_return(&self, _item: &T, _tainted: bool) {
+        self.quota.record_return(&self.caller_id);
+    }
+}
+
+/// Gives a caller's reserved quota back if the checkout is cancelled
+/// before an object is handed out.
+pub(crate) struct QuotaReservation<Q: QuotaTracker> {
+    observer: Option<QuotaObserver<Q>>,
+}
+
+impl<Q: QuotaTracker> QuotaReservation<Q> {
+    pub(crate) fn new(caller_id: &str, quota: Q) -> Self {
+        Self {
+            observer: Some(QuotaObserver {
+                caller_id: caller_id.to_owned(),
+                quota,
+            }),
+        }
+    }
+
+    /// Hands the reservation over to the checked out object.
+    pub(crate) fn into_observer(mut self) -> QuotaObserver<Q> {
+        self.observer.take().unwrap()
+    }
+}
+
+impl<Q: QuotaTracker> Drop for QuotaReservation<Q> {
+    fn drop(&mut self) {
+        if let Some(observer) = self.observer.take() {
+            observer.quota.record_return(&observer.caller_id);
+        }
+    }
+}
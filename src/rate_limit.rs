@@ -0,0 +1,51 @@
+use std::time::{Duration, Instant};
+
+/**
+Token bucket limiting how many checkouts a pool hands out per second. It
+holds at most one token, so checkouts are spread out evenly instead of
+being let through in bursts.
+*/
+pub(crate) struct TokenBucket {
+    rate: f64,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    pub(crate) fn new(rate: f64) -> Self {
+        Self {
+            rate,
+            tokens: 1.0,
+            updated: Instant::now(),
+        }
+    }
+
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(1.0);
+        self.updated = now;
+    }
+
+    /// Takes a token if one is available right now.
+    pub(crate) fn try_acquire(&mut self) -> bool {
+        self.refill();
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Reserves a token, returning how long to wait until it is usable.
+    pub(crate) fn reserve(&mut self) -> Duration {
+        self.refill();
+        self.tokens -= 1.0;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.rate)
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use std::{
+    sync::{Arc, Mutex},
+    task::Waker,
+};
+
+/**
+Tasks holding objects checked out through
+[`crate::Pool::get_exclusive_with_reentrancy_guard`], identified by their
+wakers, since tasks have no identity which is portable across runtimes.
+*/
+#[derive(Default)]
+pub(crate) struct Holders {
+    tasks: Mutex<Vec<(u64, Waker)>>,
+}
+
+impl Holders {
+    pub(crate) fn is_held_by(&self, waker: &Waker) -> bool {
+        let tasks = self.tasks.lock().unwrap();
+        tasks.iter().any(|(_, holder)| holder.will_wake(waker))
+    }
+
+    pub(crate) fn hold(self: &Arc<Self>, checkout_id: u64, waker: Waker) -> HolderGuard {
+        self.tasks.lock().unwrap().push((checkout_id, waker));
+        HolderGuard {
+            holders: self.clone(),
+            checkout_id,
+        }
+    }
+}
+
+/// Forgets the holding task once the object is released.
+pub(crate) struct HolderGuard {
+    holders: Arc<Holders>,
+    checkout_id: u64,
+}
+
+impl Drop for HolderGuard {
+    fn drop(&mut self) {
+        let mut tasks = self.holders.tasks.lock().unwrap();
+        tasks.retain(|(checkout_id, _)| *checkout_id != self.checkout_id);
+    }
+}
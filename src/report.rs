@@ -0,0 +1,72 @@
+use crate::HealthStatus;
+use std::{
+    fmt::{self, Display, Formatter},
+    time::Duration,
+};
+
+/**
+Diagnostics about a pool as returned by [`crate::Pool::health_report`],
+meant to be exposed through health check endpoints. Displays as a
+human-readable table.
+*/
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct HealthReport {
+    pub name: Option<String>,
+    pub capacity: usize,
+    pub idle: usize,
+    pub in_use: usize,
+    /// Objects the factory created since the pool was built.
+    pub total_creates: u64,
+    /// Objects released as tainted, including those failing validation.
+    pub total_taints: u64,
+    /// Consecutive factory failures since the last successful creation.
+    pub recent_failures: u32,
+    pub status: HealthStatus,
+    /// Longest time an idle object has been waiting in the pool. `None` if no
+    /// object is idle or the idle objects were locked by a waiting checkout.
+    pub oldest_idle: Option<Duration>,
+    /// Mean wait of the checkouts kept for [`crate::Pool::statistics_window`].
+    pub mean_wait: Duration,
+}
+
+#[cfg(feature = "serde")]
+impl HealthReport {
+    /// Serializes the report as a JSON object.
+    pub fn json(&self) -> String {
+        serde_json::to_string(self).expect("health report is always serializable")
+    }
+}
+
+impl Display for HealthReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let status = match self.status {
+            HealthStatus::Healthy { .. } => String::from("healthy"),
+            HealthStatus::Degraded { recent_failures } => {
+                format!("degraded ({recent_failures} recent failures)")
+            }
+        };
+        let oldest_idle = self
+            .oldest_idle
+            .map_or_else(|| String::from("-"), |age| format!("{age:?}"));
+        let rows: [(&str, String); 10] = [
+            (
+                "pool",
+                self.name.clone().unwrap_or_else(|| String::from("-")),
+            ),
+            ("status", status),
+            ("capacity", self.capacity.to_string()),
+            ("idle", self.idle.to_string()),
+            ("in use", self.in_use.to_string()),
+            ("total creates", self.total_creates.to_string()),
+            ("total taints", self.total_taints.to_string()),
+            ("recent failures", self.recent_failures.to_string()),
+            ("oldest idle", oldest_idle),
+            ("mean wait", format!("{:?}", self.mean_wait)),
+        ];
+        for (label, value) in rows {
+            writeln!(f, "{label:<16} {value}")?;
+        }
+        Ok(())
+    }
+}
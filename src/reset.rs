@@ -0,0 +1,10 @@
+use std::future::Future;
+
+/**
+Objects which can clear the state they accumulate while in use, such as
+caches of prepared statements, without being recreated. Used by
+[`crate::Pool::reset_all_idle`].
+*/
+pub trait Reset {
+    fn reset(&mut self) -> impl Future<Output = ()> + Send;
+}
@@ -0,0 +1,75 @@
+use crate::{Pooled, Result};
+use futures::{channel::oneshot, FutureExt};
+use std::{
+    future::Future,
+    ops::{Deref, DerefMut},
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+/**
+A checked out object which is handed to its [`ReleaseTask`] when dropped,
+as returned by [`crate::Pool::get_returning_guard`].
+*/
+pub struct ReturnGuard<T: Send + 'static> {
+    pooled: Option<Pooled<T>>,
+    sender: Option<oneshot::Sender<Pooled<T>>>,
+}
+
+impl<T: Send> ReturnGuard<T> {
+    pub(crate) fn new(pooled: Pooled<T>) -> (Self, ReleaseTask<T>) {
+        let (sender, receiver) = oneshot::channel();
+        let guard = Self {
+            pooled: Some(pooled),
+            sender: Some(sender),
+        };
+        (guard, ReleaseTask { receiver })
+    }
+
+    pub fn tainted(&mut self) {
+        self.pooled.as_mut().unwrap().tainted();
+    }
+}
+
+impl<T: Send> Drop for ReturnGuard<T> {
+    fn drop(&mut self) {
+        if let (Some(pooled), Some(sender)) = (self.pooled.take(), self.sender.take()) {
+            // Without a release task, the object is released as it is dropped.
+            let _ = sender.send(pooled);
+        }
+    }
+}
+
+impl<T: Send> Deref for ReturnGuard<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.pooled.as_ref().unwrap()
+    }
+}
+
+impl<T: Send> DerefMut for ReturnGuard<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.pooled.as_mut().unwrap()
+    }
+}
+
+/**
+Releases the object of a [`ReturnGuard`] once the guard is dropped,
+resolving to the outcome of the release. Meant to be spawned next to the
+code using the guard, so that release errors are not lost in `drop`.
+*/
+pub struct ReleaseTask<T: Send + 'static> {
+    receiver: oneshot::Receiver<Pooled<T>>,
+}
+
+impl<T: Send> Future for ReleaseTask<T> {
+    type Output = Result<()>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        match ready!(self.receiver.poll_unpin(cx)) {
+            Ok(mut pooled) => Poll::Ready(pooled.return_to_pool()),
+            Err(oneshot::Canceled) => Poll::Ready(Ok(())),
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use crate::slot::Slot;
+use std::{
+    collections::VecDeque,
+    ops::{Deref, DerefMut},
+    sync::{Arc, Mutex},
+};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Permits and idle objects of the checkout path behind [`crate::Pool::get_via_semaphore`].
+pub(crate) struct SemaphoreSlots<T> {
+    pub(crate) permits: Arc<Semaphore>,
+    pub(crate) idle: Mutex<VecDeque<Slot<T>>>,
+}
+
+impl<T> SemaphoreSlots<T> {
+    pub(crate) fn new(size: usize) -> Self {
+        Self {
+            permits: Arc::new(Semaphore::new(size)),
+            idle: Mutex::new(VecDeque::new()),
+        }
+    }
+}
+
+/**
+An object checked out through [`crate::Pool::get_via_semaphore`]. Goes back
+to the idle objects of that checkout path when released or dropped,
+unless it was tainted.
+*/
+pub struct SemaphorePooled<T: Send + 'static> {
+    wrapped: Option<Slot<T>>,
+    tainted: bool,
+    slots: Arc<SemaphoreSlots<T>>,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl<T: Send> SemaphorePooled<T> {
+    pub(crate) fn new(
+        slot: Slot<T>,
+        slots: Arc<SemaphoreSlots<T>>,
+        permit: OwnedSemaphorePermit,
+    ) -> Self {
+        Self {
+            wrapped: Some(slot),
+            tainted: false,
+            slots,
+            _permit: permit,
+        }
+    }
+
+    pub fn tainted(&mut self) {
+        self.tainted = true;
+    }
+
+    pub fn release(self) {}
+}
+
+impl<T: Send> Drop for SemaphorePooled<T> {
+    fn drop(&mut self) {
+        // Runs before the permit is dropped, so the object is idle by the
+        // time the next checkout gets the permit.
+        if let Some(slot) = self.wrapped.take() {
+            if !self.tainted {
+                self.slots.idle.lock().unwrap().push_back(slot);
+            }
+        }
+    }
+}
+
+impl<T: Send> Deref for SemaphorePooled<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.wrapped.as_ref().unwrap().item
+    }
+}
+
+impl<T: Send> DerefMut for SemaphorePooled<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.wrapped.as_mut().unwrap().item
+    }
+}
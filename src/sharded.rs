@@ -0,0 +1,47 @@
+use crate::{LazyPoolError, Pool, Pooled, Result};
+
+/**
+A set of independent pools, called shards, for routing checkouts with
+affinity, e.g. tenants to the shard holding their connections.
+
+```
+# use futures::executor::block_on;
+# use lazy_pool::{Pool, ShardedPool};
+
+# struct AnyObject;
+
+block_on(async {
+    let mut shards = Vec::new();
+    for _ in 0..3 {
+        shards.push(Pool::new(2, Box::new(|| AnyObject)).await.unwrap());
+    }
+    let pool = ShardedPool::new(shards);
+    let tenant = 7;
+    let object = pool.get_from_shard(tenant % pool.shard_count()).await.unwrap();
+    object.release().await.unwrap();
+});
+```
+*/
+pub struct ShardedPool<T: Send> {
+    shards: Vec<Pool<T>>,
+}
+
+impl<T: Send + 'static> ShardedPool<T> {
+    pub fn new(shards: Vec<Pool<T>>) -> Self {
+        Self { shards }
+    }
+
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    /**
+    Checks out an object from the shard at index `shard`, waiting for that
+    shard even if others have objects available. Fails with
+    [`LazyPoolError::InvalidShard`] if there is no such shard.
+    */
+    pub async fn get_from_shard(&self, shard: usize) -> Result<Pooled<T>> {
+        let pool = self.shards.get(shard).ok_or(LazyPoolError::InvalidShard)?;
+        Ok(pool.get().await)
+    }
+}
@@ -0,0 +1,35 @@
+use crate::Pooled;
+use std::{ops::Deref, sync::Arc};
+
+/**
+A checked out object which can be read by several owners at once, as
+returned by [`crate::Pool::get_shared`]. Clones share the same checkout;
+the object is released when the last clone is dropped.
+*/
+pub struct SharedPooled<T: Send + 'static> {
+    inner: Arc<Pooled<T>>,
+}
+
+impl<T: Send + 'static> SharedPooled<T> {
+    pub(crate) fn new(pooled: Pooled<T>) -> Self {
+        Self {
+            inner: Arc::new(pooled),
+        }
+    }
+}
+
+impl<T: Send> Clone for SharedPooled<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T: Send> Deref for SharedPooled<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
@@ -0,0 +1,37 @@
+use std::time::Instant;
+use uuid::Uuid;
+
+/// A pooled object along with the metadata the pool keeps about it.
+pub(crate) struct Slot<T> {
+    pub(crate) item: T,
+    pub(crate) id: Uuid,
+    pub(crate) created_at: Instant,
+    /// When the object was created or last returned to the pool.
+    pub(crate) idle_since: Instant,
+    /// Whether the object has never been returned to the pool.
+    pub(crate) fresh: bool,
+}
+
+impl<T> Slot<T> {
+    pub(crate) fn new(item: T) -> Self {
+        let now = Instant::now();
+        Self {
+            item,
+            id: Uuid::new_v4(),
+            created_at: now,
+            idle_since: now,
+            fresh: true,
+        }
+    }
+
+    /// Maps the object, keeping its metadata.
+    pub(crate) fn map<U>(self, f: impl FnOnce(T) -> U) -> Slot<U> {
+        Slot {
+            item: f(self.item),
+            id: self.id,
+            created_at: self.created_at,
+            idle_since: self.idle_since,
+            fresh: self.fresh,
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use tokio::sync::watch;
+
+/// A snapshot of the pool's usage as returned by [`crate::Pool::stats`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolStats {
+    /// Maximum number of objects the pool can hold.
+    pub capacity: usize,
+    /// Objects which have been created and are waiting in the pool.
+    pub idle: usize,
+    /// Objects which are currently checked out.
+    pub in_use: usize,
+}
+
+/// Usage counters shared between the pool and its checked out objects.
+pub(crate) struct Usage {
+    capacity: AtomicUsize,
+    idle: AtomicUsize,
+    in_use: watch::Sender<usize>,
+    retiring: AtomicUsize,
+    closed: AtomicBool,
+    detached: AtomicBool,
+    checkouts: AtomicU64,
+    checkins: AtomicU64,
+    taints: AtomicU64,
+}
+
+impl Usage {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity: AtomicUsize::new(capacity),
+            idle: AtomicUsize::new(0),
+            in_use: watch::channel(0).0,
+            retiring: AtomicUsize::new(0),
+            closed: AtomicBool::new(false),
+            detached: AtomicBool::new(false),
+            checkouts: AtomicU64::new(0),
+            checkins: AtomicU64::new(0),
+            taints: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn snapshot(&self) -> PoolStats {
+        PoolStats {
+            capacity: self.capacity.load(Ordering::SeqCst),
+            idle: self.idle.load(Ordering::SeqCst),
+            in_use: *self.in_use.borrow(),
+        }
+    }
+
+    /// Must be called when objects leave the pool together with their slots.
+    pub(crate) fn retired(&self, count: usize) {
+        self.capacity.fetch_sub(count, Ordering::SeqCst);
+    }
+
+    pub(crate) fn grown(&self, count: usize) {
+        self.capacity.fetch_add(count, Ordering::SeqCst);
+    }
+
+    /// Records slots which are to be dropped as soon as they are released.
+    pub(crate) fn retire_later(&self, count: usize) {
+        self.retiring.fetch_add(count, Ordering::SeqCst);
+    }
+
+    /// Cancels up to `count` pending retirements, returning how many were.
+    pub(crate) fn cancel_retirements(&self, count: usize) -> usize {
+        let pending = self
+            .retiring
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |pending| {
+                Some(pending.saturating_sub(count))
+            })
+            .unwrap_or_default();
+        pending.min(count)
+    }
+
+    pub(crate) fn pending_retirements(&self) -> usize {
+        self.retiring.load(Ordering::SeqCst)
+    }
+
+    /// Claims a pending retirement, if there is one.
+    pub(crate) fn take_retirement(&self) -> bool {
+        self.retiring
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |pending| {
+                pending.checked_sub(1)
+            })
+            .is_ok()
+    }
+
+    /// Marks the pool as closed, returning `false` if it already was.
+    pub(crate) fn close(&self) -> bool {
+        !self.closed.swap(true, Ordering::SeqCst)
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn set_detached(&self, detached: bool) {
+        self.detached.store(detached, Ordering::SeqCst);
+    }
+
+    /// Whether dropped objects can't be replaced, as the factory is detached.
+    pub(crate) fn is_detached(&self) -> bool {
+        self.detached.load(Ordering::SeqCst)
+    }
+
+    /// Must be called before an object is sent back to the pool.
+    pub(crate) fn returning(&self) {
+        self.idle.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Must be called after an object is received from the pool.
+    pub(crate) fn reused(&self) {
+        self.idle.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    pub(crate) fn checked_out(&self) {
+        self.checkouts.fetch_add(1, Ordering::SeqCst);
+        self.in_use.send_modify(|in_use| *in_use += 1);
+    }
+
+    pub(crate) fn checked_in(&self) {
+        self.checkins.fetch_add(1, Ordering::SeqCst);
+        self.in_use.send_modify(|in_use| *in_use -= 1);
+    }
+
+    pub(crate) fn checkouts(&self) -> u64 {
+        self.checkouts.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn checkins(&self) -> u64 {
+        self.checkins.load(Ordering::SeqCst)
+    }
+
+    /// Must be called when a tainted object is released.
+    pub(crate) fn tainted(&self) {
+        self.taints.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub(crate) fn taints(&self) -> u64 {
+        self.taints.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn subscribe_in_use(&self) -> watch::Receiver<usize> {
+        self.in_use.subscribe()
+    }
+}
@@ -0,0 +1,21 @@
+use std::time::Duration;
+
+/// How long [`crate::Pool::stress_test`] runs before giving up on the remaining operations.
+pub(crate) const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Results of [`crate::Pool::stress_test`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct StressReport {
+    /// Completed get/release cycles per second of the whole run.
+    pub throughput_ops_per_sec: f64,
+    /// Longest time a checkout waited for an object.
+    pub max_wait: Duration,
+    /// Cycles which failed to release their object, panicked or timed out.
+    pub error_count: usize,
+}
+
+/// Splits `ops` as evenly as possible between `concurrency` tasks.
+pub(crate) fn split_ops(ops: usize, concurrency: usize) -> impl Iterator<Item = usize> {
+    let concurrency = concurrency.max(1);
+    (0..concurrency).map(move |task| ops / concurrency + usize::from(task < ops % concurrency))
+}
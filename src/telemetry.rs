@@ -0,0 +1,71 @@
+use crate::stats::Usage;
+use opentelemetry::{
+    global::{self, BoxedSpan, BoxedTracer},
+    metrics::{Counter, Histogram, ObservableGauge},
+    trace::{Span, Tracer},
+    Context, KeyValue,
+};
+use std::{sync::Arc, time::Duration};
+
+/// Prefix used when none is set through [`crate::PoolBuilder::with_telemetry_prefix`].
+pub(crate) const DEFAULT_PREFIX: &str = "lazy_pool";
+
+/// OpenTelemetry instruments of a pool, created from the global providers.
+pub(crate) struct Telemetry {
+    prefix: String,
+    tracer: BoxedTracer,
+    checkouts: Counter<u64>,
+    wait_duration: Histogram<f64>,
+    _idle: ObservableGauge<u64>,
+}
+
+impl Telemetry {
+    pub(crate) fn new(prefix: &str, usage: &Arc<Usage>) -> Self {
+        let meter = global::meter("lazy-pool");
+        let usage = Arc::downgrade(usage);
+        Self {
+            prefix: prefix.to_owned(),
+            tracer: global::tracer("lazy-pool"),
+            checkouts: meter
+                .u64_counter(format!("{prefix}.pool.checkouts"))
+                .with_description("Objects checked out of the pool")
+                .build(),
+            wait_duration: meter
+                .f64_histogram(format!("{prefix}.pool.wait_duration"))
+                .with_description("Time checkouts spent waiting for an object")
+                .with_unit("s")
+                .build(),
+            _idle: meter
+                .u64_observable_gauge(format!("{prefix}.pool.idle"))
+                .with_description("Objects waiting in the pool")
+                .with_callback(move |observer| {
+                    if let Some(usage) = usage.upgrade() {
+                        observer.observe(usage.snapshot().idle as u64, &[]);
+                    }
+                })
+                .build(),
+        }
+    }
+
+    /// Starts a span for `operation`, which ends when the span is dropped.
+    pub(crate) fn span(&self, operation: &str) -> BoxedSpan {
+        self.tracer
+            .start(format!("{}.pool.{operation}", self.prefix))
+    }
+
+    /// Same as [`Telemetry::span`], as a child of the span in `parent`.
+    pub(crate) fn child_span(&self, operation: &str, parent: &Context) -> BoxedSpan {
+        self.tracer
+            .start_with_context(format!("{}.pool.{operation}", self.prefix), parent)
+    }
+
+    pub(crate) fn checked_out(&self, wait: Duration) {
+        self.checkouts.add(1, &[]);
+        self.wait_duration.record(wait.as_secs_f64(), &[]);
+    }
+}
+
+/// Records how long a checkout waited for its object on `span`.
+pub(crate) fn record_wait(span: &mut BoxedSpan, wait: Duration) {
+    span.set_attribute(KeyValue::new("pool.wait_duration", wait.as_secs_f64()));
+}
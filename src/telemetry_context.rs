@@ -0,0 +1,76 @@
+use std::time::{Duration, Instant};
+
+/**
+A span started through a [`TelemetryContext`]. The pool ends its spans
+through [`Span::end`] rather than by dropping them. Both methods do
+nothing by default.
+*/
+pub trait Span: Send + Sync {
+    /// Attaches a numeric attribute to the span.
+    fn set_attribute(&mut self, _key: &str, _value: f64) {}
+
+    fn end(&mut self) {}
+}
+
+impl Span for () {}
+
+/**
+Records the telemetry of a checkout made through
+[`crate::Pool::get_with_telemetry_context`], for any telemetry framework.
+The `()` implementation records nothing.
+
+The checkout is traced as a `pool.checkout` span, which starts when the
+checkout does and ends when the object is released. The time spent waiting
+for the object is recorded as the `pool.wait_duration` metric and span
+attribute, and the time the object was held for as the
+`pool.use_duration` metric, both in seconds.
+*/
+pub trait TelemetryContext: Send + Sync + 'static {
+    fn start_span(&self, name: &str) -> Box<dyn Span>;
+
+    fn record_metric(&self, name: &str, value: f64);
+}
+
+impl TelemetryContext for () {
+    fn start_span(&self, _name: &str) -> Box<dyn Span> {
+        Box::new(())
+    }
+
+    fn record_metric(&self, _name: &str, _value: f64) {}
+}
+
+/// The span of a checkout in progress, ended once the object is released.
+pub(crate) struct CheckoutTelemetry {
+    context: Box<dyn TelemetryContext>,
+    span: Box<dyn Span>,
+    checked_out_at: Instant,
+}
+
+impl CheckoutTelemetry {
+    pub(crate) fn start(context: Box<dyn TelemetryContext>) -> Self {
+        let span = context.start_span("pool.checkout");
+        Self {
+            context,
+            span,
+            checked_out_at: Instant::now(),
+        }
+    }
+
+    pub(crate) fn checked_out(&mut self, wait: Duration) {
+        self.context
+            .record_metric("pool.wait_duration", wait.as_secs_f64());
+        self.span
+            .set_attribute("pool.wait_duration", wait.as_secs_f64());
+        self.checked_out_at = Instant::now();
+    }
+}
+
+impl Drop for CheckoutTelemetry {
+    fn drop(&mut self) {
+        self.context.record_metric(
+            "pool.use_duration",
+            self.checked_out_at.elapsed().as_secs_f64(),
+        );
+        self.span.end();
+    }
+}
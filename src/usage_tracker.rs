@@ -0,0 +1,68 @@
+use crate::{CheckoutState, Pooled, PooledWith, Result};
+
+/**
+Tracks resources objects consume per checkout, such as bytes transferred
+or queries executed, for checkouts made through
+[`crate::Pool::get_with_usage_tracker`]. Implemented for mutable
+references to trackers, so that one tracker can accumulate usage across
+checkouts.
+*/
+pub trait UsageTracker<T> {
+    /// Called when the object is handed out.
+    fn record_checkout(&mut self, item: &T);
+
+    /// Called when the object is released, with the usage reported for it.
+    fn record_return(&mut self, item: &T, usage: u64);
+}
+
+impl<T, U: UsageTracker<T> + ?Sized> UsageTracker<T> for &mut U {
+    fn record_checkout(&mut self, item: &T) {
+        (**self).record_checkout(item);
+    }
+
+    fn record_return(&mut self, item: &T, usage: u64) {
+        (**self).record_return(item, usage);
+    }
+}
+
+/**
+An object checked out through [`crate::Pool::get_with_usage_tracker`].
+The usage reported through [`TrackedPooled::report_usage`] is passed to
+the tracker when the object is released or dropped.
+*/
+pub type TrackedPooled<T, U> = PooledWith<T, Tracked<U>>;
+
+/// Tracker and reported usage of a [`TrackedPooled`] object.
+pub struct Tracked<U> {
+    tracker: U,
+    usage: u64,
+}
+
+impl<U> Tracked<U> {
+    pub(crate) fn new(tracker: U) -> Self {
+        Self { tracker, usage: 0 }
+    }
+}
+
+impl<T: Send + 'static, U: UsageTracker<T>> CheckoutState<T> for Tracked<U> {
+    type Released = Result<()>;
+
+    fn after_checkout(&mut self, pooled: &Pooled<T>) {
+        self.tracker.record_checkout(pooled);
+    }
+
+    fn before_release(&mut self, pooled: &mut Pooled<T>) {
+        self.tracker.record_return(pooled, self.usage);
+    }
+
+    fn released(&mut self, result: Result<()>) -> Result<()> {
+        result
+    }
+}
+
+impl<T: Send, U: UsageTracker<T>> TrackedPooled<T, U> {
+    /// Adds `units` to the usage of this checkout.
+    pub fn report_usage(&mut self, units: u64) {
+        self.state_mut().usage += units;
+    }
+}
@@ -0,0 +1,90 @@
+use futures::channel::oneshot;
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::BinaryHeap,
+    sync::Mutex,
+};
+
+/// Priority of a checkout made through [`crate::Pool::get_with_priority`].
+/// Lower values are served first.
+pub type Priority = u32;
+
+struct Waiter<T> {
+    key: (Reverse<Priority>, Reverse<u64>),
+    sender: oneshot::Sender<T>,
+}
+
+impl<T> PartialEq for Waiter<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl<T> Eq for Waiter<T> {}
+
+impl<T> PartialOrd for Waiter<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Waiter<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+struct Queue<T> {
+    heap: BinaryHeap<Waiter<T>>,
+    next_sequence: u64,
+}
+
+/**
+Checkouts waiting for a returned object, ordered by priority and then by
+arrival. Released objects are handed to them before going back to the pool.
+*/
+pub(crate) struct Waiters<T> {
+    queue: Mutex<Queue<T>>,
+}
+
+impl<T> Waiters<T> {
+    pub(crate) fn new() -> Self {
+        Self {
+            queue: Mutex::new(Queue {
+                heap: BinaryHeap::new(),
+                next_sequence: 0,
+            }),
+        }
+    }
+
+    pub(crate) fn enqueue(&self, priority: Priority) -> oneshot::Receiver<T> {
+        let mut queue = self.queue.lock().unwrap();
+        // Drop waiters whose checkouts were cancelled.
+        queue.heap.retain(|waiter| !waiter.sender.is_canceled());
+        let (sender, receiver) = oneshot::channel();
+        let sequence = queue.next_sequence;
+        queue.next_sequence += 1;
+        queue.heap.push(Waiter {
+            key: (Reverse(priority), Reverse(sequence)),
+            sender,
+        });
+        receiver
+    }
+
+    /// Hands `item` to the first waiter, or gives it back if there is none.
+    pub(crate) fn serve(&self, mut item: T) -> Result<(), T> {
+        let mut queue = self.queue.lock().unwrap();
+        while let Some(waiter) = queue.heap.pop() {
+            match waiter.sender.send(item) {
+                Ok(()) => return Ok(()),
+                Err(returned) => item = returned,
+            }
+        }
+        Err(item)
+    }
+
+    #[cfg(test)]
+    pub(crate) fn len(&self) -> usize {
+        self.queue.lock().unwrap().heap.len()
+    }
+}
@@ -0,0 +1,118 @@
+use std::{
+    collections::VecDeque,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Pool activity over a period of time, see [`crate::Pool::statistics_window`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct WindowedStats {
+    pub checkouts_per_second: f64,
+    pub creates_per_second: f64,
+    /// Mean time checkouts spent waiting for an object.
+    pub mean_wait_ms: f64,
+    /// Fraction of object creations which failed.
+    pub error_rate: f64,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Activity {
+    Checkout { wait: Duration },
+    Create,
+    CreateFailed,
+}
+
+/// Ring buffer of the most recent pool activity.
+pub(crate) struct ActivityLog {
+    entries: Mutex<VecDeque<(Instant, Activity)>>,
+    capacity: usize,
+}
+
+impl ActivityLog {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            entries: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity,
+        }
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub(crate) fn record(&self, activity: Activity) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() == self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back((Instant::now(), activity));
+    }
+
+    /// Time each of the buffered checkouts spent waiting for an object.
+    pub(crate) fn waits(&self) -> Vec<Duration> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .iter()
+            .filter_map(|(_, activity)| match activity {
+                Activity::Checkout { wait } => Some(*wait),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Mean time the buffered checkouts spent waiting for an object.
+    pub(crate) fn mean_wait(&self) -> Duration {
+        let waits = self.waits();
+        if waits.is_empty() {
+            return Duration::ZERO;
+        }
+        waits.iter().sum::<Duration>() / waits.len() as u32
+    }
+
+    pub(crate) fn window(&self, window: Duration) -> WindowedStats {
+        let now = Instant::now();
+        let (mut checkouts, mut creates, mut failures) = (0u32, 0u32, 0u32);
+        let mut total_wait = Duration::ZERO;
+        let entries = self.entries.lock().unwrap();
+        let recent = entries
+            .iter()
+            .rev()
+            .take_while(|(at, _)| now.duration_since(*at) <= window);
+        for (_, activity) in recent {
+            match activity {
+                Activity::Checkout { wait } => {
+                    checkouts += 1;
+                    total_wait += *wait;
+                }
+                Activity::Create => creates += 1,
+                Activity::CreateFailed => failures += 1,
+            }
+        }
+        let seconds = window.as_secs_f64();
+        let per_second = |count: u32| {
+            if seconds > 0.0 {
+                f64::from(count) / seconds
+            } else {
+                0.0
+            }
+        };
+        let attempts = creates + failures;
+        WindowedStats {
+            checkouts_per_second: per_second(checkouts),
+            creates_per_second: per_second(creates),
+            mean_wait_ms: if checkouts > 0 {
+                total_wait.as_secs_f64() * 1000.0 / f64::from(checkouts)
+            } else {
+                0.0
+            },
+            error_rate: if attempts > 0 {
+                f64::from(failures) / f64::from(attempts)
+            } else {
+                0.0
+            },
+        }
+    }
+}
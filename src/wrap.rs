@@ -0,0 +1,103 @@
+use crate::{slot::Slot, Pool, Pooled, Result};
+use std::{
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+};
+use uuid::Uuid;
+
+/**
+Wrappers which pooled objects are handed out in by a [`WrappedPool`],
+e.g. to roll back uncommitted work on a connection before it goes back
+into the pool. Objects are only returned to the pool through
+[`Wrap::unwrap`], so cleanup which a wrapper would do when dropped must be
+done there instead.
+*/
+pub trait Wrap<T>: Sized {
+    fn wrap(item: T) -> Self;
+
+    fn unwrap(self) -> T;
+}
+
+/**
+A view of a pool whose objects are wrapped in `W` while checked out, as
+returned by [`crate::Pool::with_object_wrapper`].
+*/
+pub struct WrappedPool<'a, T: Send, W> {
+    pool: &'a Pool<T>,
+    wrapper: PhantomData<fn() -> W>,
+}
+
+impl<'a, T: Send + 'static, W: Wrap<T>> WrappedPool<'a, T, W> {
+    pub(crate) fn new(pool: &'a Pool<T>) -> Self {
+        Self {
+            pool,
+            wrapper: PhantomData,
+        }
+    }
+
+    /// Same as [`Pool::get`], with the object wrapped.
+    pub async fn get(&self) -> WrappedPooled<T, W> {
+        WrappedPooled::new(self.pool.get().await)
+    }
+
+    /// Same as [`Pool::try_get`], with the object wrapped.
+    pub fn try_get(&self) -> Option<WrappedPooled<T, W>> {
+        self.pool.try_get().map(WrappedPooled::new)
+    }
+}
+
+/**
+An object checked out through a [`WrappedPool`]. The object is unwrapped
+when it is released or dropped.
+*/
+pub struct WrappedPooled<T: Send + 'static, W: Wrap<T>> {
+    pooled: Pooled<T>,
+    wrapped: Option<Slot<W>>,
+}
+
+impl<T: Send, W: Wrap<T>> WrappedPooled<T, W> {
+    fn new(mut pooled: Pooled<T>) -> Self {
+        let wrapped = pooled.wrapped.take().map(|slot| slot.map(W::wrap));
+        Self { pooled, wrapped }
+    }
+
+    pub fn tainted(&mut self) {
+        self.pooled.tainted();
+    }
+
+    pub fn object_id(&self) -> Uuid {
+        self.wrapped.as_ref().unwrap().id
+    }
+
+    pub async fn release(mut self) -> Result<()> {
+        self.unwrap_into_pooled();
+        self.pooled.return_to_pool()
+    }
+
+    fn unwrap_into_pooled(&mut self) {
+        if let Some(slot) = self.wrapped.take() {
+            self.pooled.wrapped = Some(slot.map(W::unwrap));
+        }
+    }
+}
+
+impl<T: Send, W: Wrap<T>> Drop for WrappedPooled<T, W> {
+    fn drop(&mut self) {
+        // Hands the object back to `pooled`, which releases it when dropped.
+        self.unwrap_into_pooled();
+    }
+}
+
+impl<T: Send, W: Wrap<T>> Deref for WrappedPooled<T, W> {
+    type Target = W;
+
+    fn deref(&self) -> &W {
+        &self.wrapped.as_ref().unwrap().item
+    }
+}
+
+impl<T: Send, W: Wrap<T>> DerefMut for WrappedPooled<T, W> {
+    fn deref_mut(&mut self) -> &mut W {
+        &mut self.wrapped.as_mut().unwrap().item
+    }
+}
@@ -0,0 +1,98 @@
+//! Stress tests exercising the pool under high concurrency. These take a
+//! while, so they are ignored by default. Run them with:
+//!
+//! ```text
+//! cargo test --test stress -- --ignored
+//! ```
+
+use futures_timer::Delay;
+use lazy_pool::{get, Pool};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::task::JoinSet;
+
+const POOL_SIZE: usize = 5;
+const TASKS: usize = 200;
+
+#[derive(Default)]
+struct Counter {
+    current: AtomicUsize,
+    max: AtomicUsize,
+}
+
+impl Counter {
+    fn increment(&self) {
+        let current = self.current.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max.fetch_max(current, Ordering::SeqCst);
+    }
+
+    fn decrement(&self) {
+        self.current.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+struct Tracked {
+    alive: Arc<Counter>,
+    checked_out: Arc<Counter>,
+}
+
+impl Tracked {
+    fn new(alive: Arc<Counter>, checked_out: Arc<Counter>) -> Self {
+        alive.increment();
+        Self { alive, checked_out }
+    }
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        self.alive.decrement();
+    }
+}
+
+fn random_delay() -> Duration {
+    Duration::from_millis(u64::from(uuid::Uuid::new_v4().as_bytes()[0] % 10))
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+#[ignore]
+async fn concurrent_checkouts_never_exceed_pool_size() {
+    let alive = Arc::new(Counter::default());
+    let checked_out = Arc::new(Counter::default());
+    let factory_alive = alive.clone();
+    let factory_checked_out = checked_out.clone();
+    let pool = Arc::new(
+        Pool::new(POOL_SIZE, move || {
+            Tracked::new(factory_alive.clone(), factory_checked_out.clone())
+        })
+        .await
+        .unwrap(),
+    );
+
+    let completed = Arc::new(AtomicUsize::new(0));
+    let mut join_set = JoinSet::new();
+    for _ in 0..TASKS {
+        let local_pool = pool.clone();
+        let local_completed = completed.clone();
+        join_set.spawn(async move {
+            get!(item = local_pool => {
+                item.checked_out.increment();
+                Delay::new(random_delay()).await;
+                item.checked_out.decrement();
+            });
+            local_completed.fetch_add(1, Ordering::SeqCst);
+        });
+    }
+    while let Some(result) = join_set.join_next().await {
+        result.expect("task panicked");
+    }
+
+    assert_eq!(TASKS, completed.load(Ordering::SeqCst));
+    assert!(alive.max.load(Ordering::SeqCst) <= POOL_SIZE);
+    assert!(checked_out.max.load(Ordering::SeqCst) <= POOL_SIZE);
+    assert_eq!(0, pool.stats().in_use);
+}